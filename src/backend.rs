@@ -0,0 +1,176 @@
+//! Servers whose secret key is held by an external keystore.
+//!
+//! A [`Server`] runs the server side of the protocol in all three modes with a [`KeyBackend`], to
+//! which it delegates every operation involving the secret key `k`: multiplying elements by `k` or
+//! by the inverse of the tweaked key `k + m`, and answering the challenge of a DLEQ proof. The
+//! backend can be an HSM, a KMS or an enclave, and the secret key never leaves it. The trait is
+//! object safe, so the backend can also be chosen at runtime as a `&dyn KeyBackend<S, Error = E>`.
+//!
+//! The [`SecretKey`] held in memory by a default [`Server`] is itself a backend, and its `evaluate`
+//! methods go through the same path as the `try_evaluate` methods of a server with any other
+//! backend: with the same randomness, both produce the same evaluations and proofs.
+//!
+//! [`Server`]: crate::server::Server
+
+use group::{Group, ff::Field};
+use rand_core::RngCore;
+
+use crate::{
+    Proof, Suite, VerifyingKey,
+    server::{SecretKey, UndefinedInverse},
+};
+
+/// Operations on the secret key `k` of a server.
+pub trait KeyBackend<S: Suite> {
+    /// The error returned by the operations of the backend.
+    type Error: From<UndefinedInverse>;
+
+    /// The verifying key, i.e. `G * k`.
+    fn verifying_key(&self) -> VerifyingKey<S::Group>;
+
+    /// Multiply the `elements` by `k`, in place.
+    fn multiply(&self, elements: &mut [S::Group]) -> Result<(), Self::Error>;
+
+    /// Multiply the `elements` by the inverse of `k + tweak`, in place.
+    ///
+    /// Fails with [`UndefinedInverse`] if `k + tweak` is zero.
+    fn multiply_inverse(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        elements: &mut [S::Group],
+    ) -> Result<(), Self::Error>;
+
+    /// Prove that the composite `z` is the composite `m` multiplied by `k + tweak`.
+    ///
+    /// The backend computes `z = m * (k + tweak)`, draws a nonce `r`, calls `challenge` once with
+    /// `z` and the commitments `G * r` and `m * r`, and returns the [`Proof`]
+    /// `(c, r - c * (k + tweak))` for the challenge `c`. A remote backend can return the
+    /// commitments in a first request, and the response in a second one.
+    ///
+    /// Backends with their own source of randomness may ignore `rng`, but the nonce must be
+    /// uniformly random and never reused, otherwise the proofs leak the secret key.
+    fn prove(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        m: S::Group,
+        challenge: &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+        rng: &mut dyn RngCore,
+    ) -> Result<Proof<<S::Group as Group>::Scalar>, Self::Error>;
+}
+
+impl<S: Suite> KeyBackend<S> for SecretKey<<S::Group as Group>::Scalar> {
+    type Error = UndefinedInverse;
+
+    fn verifying_key(&self) -> VerifyingKey<S::Group> {
+        VerifyingKey(S::Group::mul_by_generator(self.expose_secret()))
+    }
+
+    fn multiply(&self, elements: &mut [S::Group]) -> Result<(), UndefinedInverse> {
+        let key = *self.expose_secret();
+        elements.iter_mut().for_each(|e| *e *= key);
+        Ok(())
+    }
+
+    fn multiply_inverse(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        elements: &mut [S::Group],
+    ) -> Result<(), UndefinedInverse> {
+        let t = *self.expose_secret() + tweak;
+        let t_inv = t.invert().into_option().ok_or(UndefinedInverse)?;
+        elements.iter_mut().for_each(|e| *e *= t_inv);
+        Ok(())
+    }
+
+    fn prove(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        m: S::Group,
+        challenge: &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+        rng: &mut dyn RngCore,
+    ) -> Result<Proof<<S::Group as Group>::Scalar>, UndefinedInverse> {
+        Ok(prove_with::<S>(
+            *self.expose_secret() + tweak,
+            m,
+            challenge,
+            |_, _, _| <S::Group as Group>::Scalar::random(rng),
+        ))
+    }
+}
+
+impl<S: Suite, B: KeyBackend<S> + ?Sized> KeyBackend<S> for &B {
+    type Error = B::Error;
+
+    fn verifying_key(&self) -> VerifyingKey<S::Group> {
+        (**self).verifying_key()
+    }
+
+    fn multiply(&self, elements: &mut [S::Group]) -> Result<(), B::Error> {
+        (**self).multiply(elements)
+    }
+
+    fn multiply_inverse(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        elements: &mut [S::Group],
+    ) -> Result<(), B::Error> {
+        (**self).multiply_inverse(tweak, elements)
+    }
+
+    fn prove(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        m: S::Group,
+        challenge: &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+        rng: &mut dyn RngCore,
+    ) -> Result<Proof<<S::Group as Group>::Scalar>, B::Error> {
+        (**self).prove(tweak, m, challenge, rng)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Suite, B: KeyBackend<S> + ?Sized> KeyBackend<S> for alloc::boxed::Box<B> {
+    type Error = B::Error;
+
+    fn verifying_key(&self) -> VerifyingKey<S::Group> {
+        (**self).verifying_key()
+    }
+
+    fn multiply(&self, elements: &mut [S::Group]) -> Result<(), B::Error> {
+        (**self).multiply(elements)
+    }
+
+    fn multiply_inverse(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        elements: &mut [S::Group],
+    ) -> Result<(), B::Error> {
+        (**self).multiply_inverse(tweak, elements)
+    }
+
+    fn prove(
+        &self,
+        tweak: &<S::Group as Group>::Scalar,
+        m: S::Group,
+        challenge: &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+        rng: &mut dyn RngCore,
+    ) -> Result<Proof<<S::Group as Group>::Scalar>, B::Error> {
+        (**self).prove(tweak, m, challenge, rng)
+    }
+}
+
+/// [`KeyBackend::prove`] with the key `t = k + tweak` in memory, and the nonce computed by
+/// `nonce(t, m, z)`.
+pub(crate) fn prove_with<S: Suite>(
+    t: <S::Group as Group>::Scalar,
+    m: S::Group,
+    challenge: &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+    nonce: impl FnOnce(&<S::Group as Group>::Scalar, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+) -> Proof<<S::Group as Group>::Scalar> {
+    let z = m * t;
+    let r = nonce(&t, m, z);
+    let c = challenge(z, S::Group::mul_by_generator(&r), m * r);
+    let s = r - c * t;
+
+    Proof { c, s }
+}
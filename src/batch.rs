@@ -0,0 +1,219 @@
+//! Batch verification of many proofs, across sessions and servers.
+//!
+//! [`verify`] checks a collection of [`Receipt`]s, possibly from different servers and in both the
+//! [`Verifiable`] and [`Partial`] modes, and reports which one is invalid when the batch fails.
+//!
+//! **Only receipts with [`Commitments`] are verified faster than one by one.** The proofs of RFC
+//! 9497, from [`Server::evaluate`](crate::server::Server::evaluate), carry no commitments, and a
+//! batch of them costs about as much as verifying each of them.
+//!
+//! Receipts with the [`Commitments`] of their proof, from
+//! [`Server::evaluate_committed`](crate::server::Server::evaluate_committed), are folded: their
+//! challenge is checked with a hash, and the equations of all of them
+//!
+//! ```text
+//! t2 = s * G + c * B    t3 = s * M + c * Z    M = Σ di * Ci    Z = Σ di * Di
+//! ```
+//!
+//! are combined with random weights into a single multi-scalar multiplication, which is the
+//! identity if all of them hold, and is not with overwhelming probability otherwise. Only when it
+//! fails are the receipts checked one by one, to find the first invalid one.
+//!
+//! The proofs of RFC 9497 alone are in the compact form `(c, s)`: the verifier recomputes the
+//! commitments `t2` and `t3` and hashes them into the challenge, and a hash can't be folded into
+//! a linear combination. Receipts without commitments are therefore checked on their own, only
+//! sharing with the rest of the batch the tweaked key of the [`Partial`] mode and the seed of the
+//! composites of each server.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use digest::Output;
+use group::{Group, GroupEncoding, ff::Field};
+use rand_core::RngCore;
+
+use crate::{
+    Commitments, Proof, Suite, challenge, composite_scalars,
+    mode::{Mode, Partial, Verifiable},
+    receipt::Receipt,
+};
+
+/// Verify all the `receipts`.
+///
+/// Only the receipts with [`Commitments`] are folded into a single multi-scalar multiplication.
+/// Receipts with the compact proofs of RFC 9497 are verified one by one, so a batch of them is not
+/// faster than [`Receipt::verify`] on each of them. See the [module documentation](self).
+///
+/// The weights of the combination are drawn from `rng`, which must be unpredictable to the servers.
+///
+/// Returns the index of the first invalid receipt if any.
+#[allow(clippy::type_complexity)]
+pub fn verify<S: Suite>(receipts: &[Receipt<S>], rng: &mut impl RngCore) -> Result<(), BatchError> {
+    // The key and the seed of the composites of each server, or `None` if its tweaked key is
+    // invalid.
+    let mut keys: BTreeMap<(Vec<u8>, Option<&[u8]>), Option<(S::Group, Output<S::Hash>)>> =
+        BTreeMap::new();
+    let mut combination = Combination::<S>::default();
+
+    for (index, receipt) in receipts.iter().enumerate() {
+        let id = (
+            receipt.verifying_key.0.to_bytes().as_ref().to_vec(),
+            receipt.info.as_deref(),
+        );
+        let key = keys.entry(id).or_insert_with(|| {
+            let key = receipt.key().ok()?;
+            Some((key, receipt.seed(key)))
+        });
+        let valid = match key {
+            None => false,
+            Some((key, seed)) => match receipt.commitments {
+                Some(commitments) => combination.fold(receipt, *key, seed, commitments, rng),
+                None => receipt.verify_with_seed(*key, seed).is_ok(),
+            },
+        };
+        if !valid {
+            return Err(first_invalid(&receipts[..index]).unwrap_or(BatchError { index }));
+        }
+    }
+
+    if !combination.holds() {
+        let last = receipts.len() - 1;
+        return Err(first_invalid(&receipts[..last]).unwrap_or(BatchError { index: last }));
+    }
+
+    Ok(())
+}
+
+/// Check the `receipts` one by one, and return the index of the first invalid one.
+fn first_invalid<S: Suite>(receipts: &[Receipt<S>]) -> Option<BatchError> {
+    receipts
+        .iter()
+        .position(|receipt| receipt.verify().is_err())
+        .map(|index| BatchError { index })
+}
+
+/// A random linear combination of the equations of the proofs with commitments.
+struct Combination<S: Suite> {
+    /// The scalar of the generator, shared by all the proofs.
+    generator: <S::Group as Group>::Scalar,
+    scalars: Vec<<S::Group as Group>::Scalar>,
+    elements: Vec<S::Group>,
+}
+
+impl<S: Suite> Default for Combination<S> {
+    fn default() -> Self {
+        Combination {
+            generator: <S::Group as Group>::Scalar::ZERO,
+            scalars: Vec::new(),
+            elements: Vec::new(),
+        }
+    }
+}
+
+impl<S: Suite> Combination<S> {
+    /// Check the challenge of the proof of the `receipt`, and add its equations to the
+    /// combination.
+    ///
+    /// Returns `false` if the receipt is invalid.
+    fn fold(
+        &mut self,
+        receipt: &Receipt<S>,
+        key: S::Group,
+        seed: &Output<S::Hash>,
+        commitments: Commitments<S::Group>,
+        rng: &mut impl RngCore,
+    ) -> bool {
+        if receipt.blinded_elements.len() != receipt.evaluated_elements.len()
+            || receipt.blinded_elements.len() > u16::MAX as usize
+        {
+            return false;
+        }
+        let blinded_elements: Vec<_> = receipt.blinded_elements.iter().map(|b| b.0).collect();
+        let evaluated_elements: Vec<_> = receipt.evaluated_elements.iter().map(|e| e.0).collect();
+
+        match receipt.info {
+            None => self.fold_proof::<Verifiable>(
+                key,
+                seed,
+                &blinded_elements,
+                &evaluated_elements,
+                receipt.proof,
+                commitments,
+                rng,
+            ),
+            Some(_) => self.fold_proof::<Partial>(
+                key,
+                seed,
+                &evaluated_elements,
+                &blinded_elements,
+                receipt.proof,
+                commitments,
+                rng,
+            ),
+        }
+    }
+
+    /// [`fold`](Self::fold) for a proof that `key = k * G` and `d = k * c`.
+    #[allow(clippy::too_many_arguments)]
+    fn fold_proof<M: Mode>(
+        &mut self,
+        key: S::Group,
+        seed: &Output<S::Hash>,
+        c: &[S::Group],
+        d: &[S::Group],
+        proof: Proof<<S::Group as Group>::Scalar>,
+        commitments: Commitments<S::Group>,
+        rng: &mut impl RngCore,
+    ) -> bool {
+        let Commitments { m, z, t2, t3 } = commitments;
+        if challenge::<S, M>(key, m, z, t2, t3) != proof.c {
+            return false;
+        }
+
+        let [w2, w3, wm, wz] =
+            core::array::from_fn(|_| <S::Group as Group>::Scalar::random(&mut *rng));
+        // t2 = s * G + c * B
+        self.generator += w2 * proof.s;
+        self.push(w2 * proof.c, key);
+        self.push(-w2, t2);
+        // t3 = s * M + c * Z, with M = Σ di * Ci and Z = Σ di * Di
+        self.push(w3 * proof.s - wm, m);
+        self.push(w3 * proof.c - wz, z);
+        self.push(-w3, t3);
+        composite_scalars::<S, M>(seed, c, d, |di, c, d| {
+            for ((di, c), d) in di.iter().zip(c).zip(d) {
+                self.push(wm * di, *c);
+                self.push(wz * di, *d);
+            }
+        });
+
+        true
+    }
+
+    fn push(&mut self, scalar: <S::Group as Group>::Scalar, element: S::Group) {
+        self.scalars.push(scalar);
+        self.elements.push(element);
+    }
+
+    /// Whether all the folded equations hold.
+    fn holds(mut self) -> bool {
+        self.push(self.generator, S::Group::generator());
+        S::multiscalar_mul(&self.scalars, &self.elements)
+            .is_identity()
+            .into()
+    }
+}
+
+/// Error verifying a batch of proofs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BatchError {
+    /// The index of the first invalid receipt in the batch.
+    pub index: usize,
+}
+
+impl core::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the proof at index {} is invalid", self.index)
+    }
+}
+
+impl core::error::Error for BatchError {}
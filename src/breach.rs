@@ -0,0 +1,185 @@
+//! Compromised credential checking on top of the [`Partial`] mode.
+//!
+//! The server partitions a database of leaked credentials into [`Bucket`]s by a prefix of the
+//! credential's hash. The prefix is used as the shared `info` of the POPRF, so that the client only
+//! reveals which bucket its credential falls into, and the server only reveals the PRF outputs of
+//! the leaked credentials in that bucket.
+//!
+//! The flow is as follows:
+//! 1. The server builds a [`Database`] of leaked credentials with [`Database::insert`].
+//! 2. The client computes the [`Bucket`] of its credential, and starts a [`Check`].
+//! 3. The server evaluates the blinded element with [`Database::evaluate`], and sends back the
+//!    entries of the [`Bucket`].
+//! 4. The client finishes the [`Check`] with the evaluated element, the proof and the entries.
+//!
+//! A credential is any byte string, for example the concatenation of a normalized username and a
+//! password. It is up to the application to encode credentials consistently on both sides.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use digest::{Digest, Output, OutputSizeUser, typenum::Unsigned};
+use group::Group;
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    mode::Partial,
+    server::{EvaluationError, Server, UndefinedInverse},
+};
+
+/// The bucket a credential falls into.
+///
+/// This is the first `L` bytes of the hash of the credential. Smaller values of `L` reveal less
+/// information about the credential to the server, at the cost of larger buckets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bucket<const L: usize>(pub [u8; L]);
+
+impl<const L: usize> Bucket<L> {
+    /// Compute the bucket of the provided credential.
+    ///
+    /// Fails to compile if `L` is larger than the output size of the suite's hash function.
+    pub fn of<S: Suite>(credential: Input<'_>) -> Self {
+        const {
+            assert!(
+                L <= <<S::Hash as OutputSizeUser>::OutputSize as Unsigned>::USIZE,
+                "the bucket is larger than the output of the hash function"
+            )
+        };
+        let digest = S::Hash::new()
+            .chain_update((credential.as_ref().len() as u16).to_be_bytes())
+            .chain_update(credential.as_ref())
+            .chain_update("Bucket")
+            .finalize();
+        let mut prefix = [0; L];
+        prefix.copy_from_slice(&digest[..L]);
+        Bucket(prefix)
+    }
+
+    /// The bucket as the shared `info` of the POPRF.
+    pub fn info(&self) -> Input<'_> {
+        Input(&self.0)
+    }
+}
+
+/// Server side database of leaked credentials.
+///
+/// Holds the PRF outputs of the leaked credentials, grouped by [`Bucket`].
+#[derive(Clone, Debug)]
+pub struct Database<S: Suite, const L: usize> {
+    server: Server<S, Partial>,
+    buckets: BTreeMap<Bucket<L>, Vec<Output<S::Hash>>>,
+}
+
+impl<S: Suite, const L: usize> Database<S, L> {
+    /// Create an empty database evaluated with the provided server.
+    pub fn new(server: Server<S, Partial>) -> Self {
+        Database {
+            server,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// The underlying server.
+    pub fn server(&self) -> &Server<S, Partial> {
+        &self.server
+    }
+
+    /// The verifying key clients should use when starting a [`Check`].
+    pub fn verifying_key(&self) -> VerifyingKey<S::Group> {
+        self.server.verifying_key()
+    }
+
+    /// Add a leaked credential to the database.
+    pub fn insert(&mut self, credential: Input<'_>) -> Result<(), EvaluationError> {
+        let bucket = Bucket::of::<S>(credential);
+        let [output] = self.server.full_evaluate([credential], bucket.info())?;
+        let entries = self.buckets.entry(bucket).or_default();
+        if let Err(index) = entries.binary_search(&output) {
+            entries.insert(index, output);
+        }
+        Ok(())
+    }
+
+    /// Add many leaked credentials to the database.
+    ///
+    /// Stops at the first credential that fails to be evaluated.
+    pub fn extend<'a>(
+        &mut self,
+        credentials: impl IntoIterator<Item = Input<'a>>,
+    ) -> Result<(), EvaluationError> {
+        credentials
+            .into_iter()
+            .try_for_each(|credential| self.insert(credential))
+    }
+
+    /// The entries of the provided bucket, sorted.
+    ///
+    /// This is what the server sends to the client alongside the evaluated element.
+    pub fn bucket(&self, bucket: &Bucket<L>) -> &[Output<S::Hash>] {
+        self.buckets
+            .get(bucket)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The number of leaked credentials in the database.
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(Vec::len).sum()
+    }
+
+    /// Whether the database contains no leaked credentials.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Evaluate the blinded element of a client, in the provided bucket.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate(
+        &self,
+        blinded_element: Blinded<S::Group>,
+        bucket: &Bucket<L>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Evaluated<S::Group>, Proof<<S::Group as Group>::Scalar>), UndefinedInverse> {
+        let ([evaluated_element], proof) =
+            self.server
+                .evaluate([blinded_element], bucket.info(), rng)?;
+        Ok((evaluated_element, proof))
+    }
+}
+
+/// Client side check of a credential against a [`Database`].
+pub struct Check<'a, S: Suite> {
+    client: Client<'a, 'a, 1, S, Partial>,
+}
+
+impl<'a, S: Suite> Check<'a, S> {
+    /// Start checking a credential.
+    ///
+    /// The `bucket` must be the [`Bucket`] of the `credential`, and is sent to the server alongside
+    /// the blinded element.
+    pub fn new<const L: usize>(
+        credential: Input<'a>,
+        bucket: &'a Bucket<L>,
+        verifying_key: VerifyingKey<S::Group>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, Blinded<S::Group>), InvalidInput> {
+        let (client, [blinded_element]) =
+            Client::<_, S, Partial>::blind([credential], bucket.info(), verifying_key, rng)?;
+        Ok((Check { client }, blinded_element))
+    }
+
+    /// Finish the check.
+    ///
+    /// Returns whether the credential is part of the `entries` of its bucket, i.e., whether it was
+    /// compromised.
+    pub fn finish(
+        self,
+        evaluated_element: Evaluated<S::Group>,
+        proof: Proof<<S::Group as Group>::Scalar>,
+        entries: &[Output<S::Hash>],
+    ) -> Result<bool, InvalidProof> {
+        let [output] = self.client.finalize([evaluated_element], proof)?;
+        Ok(entries.contains(&output))
+    }
+}
@@ -2,14 +2,18 @@
 //!
 //! This module contains the [`Client`] type, and errors that the client may return.
 
-use digest::{Digest, Output};
-use group::{Group, GroupEncoding, ff::Field};
+use digest::Output;
+use group::{Group, ff::Field};
 use rand_core::RngCore;
 
 use crate::{
-    Blinded, Evaluated, Input, Mode, Proof, Suite, VerifyingKey, hash_to_group, hash_to_scalar, mode,
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey, finalize_hash, hash_inputs, info_scalar,
+    map_array,
+    mode::{self, ClientMode},
     verify_proof,
 };
+#[cfg(feature = "alloc")]
+use crate::{composite_seed, prepared::PreparedVerifyingKey, verify_proof_with};
 
 /// Client of the OPRF protocol.
 ///
@@ -33,22 +37,29 @@ use crate::{
 /// [`mode::Base`]: #impl-Client<'a,+'b,+N,+S,+Base>
 /// [`mode::Verifiable`]: #impl-Client<'a,+'b,+N,+S,+Verifiable>
 /// [`mode::Partial`]: #impl-Client<'a,+'b,+N,+S,+Partial>
-#[allow(private_bounds)]
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Client<'a, 'b, const N: usize, S: Suite, M: Mode> {
+#[derive(Clone, PartialEq, Eq)]
+pub struct Client<'a, 'b, const N: usize, S: Suite, M: ClientMode> {
     blinds: [<S::Group as Group>::Scalar; N],
     inputs: [Input<'a>; N],
     payload: M::ClientPayload<'b, N, S::Group>,
 }
 
-impl<const N: usize, S: Suite, M: Mode> zeroize::Zeroize for Client<'_, '_, N, S, M> {
+impl<const N: usize, S: Suite, M: ClientMode> core::fmt::Debug for Client<'_, '_, N, S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // The blinds are secret, and reveal the inputs from the blinded elements.
+        f.debug_struct("Client")
+            .field("inputs", &self.inputs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const N: usize, S: Suite, M: ClientMode> zeroize::Zeroize for Client<'_, '_, N, S, M> {
     fn zeroize(&mut self) {
         self.blinds = core::array::from_fn(|_| <S::Group as Group>::Scalar::ZERO);
     }
 }
 
-#[allow(private_bounds)]
-impl<'a, 'b, const N: usize, S: Suite, M: Mode> Client<'a, 'b, N, S, M> {
+impl<'a, 'b, const N: usize, S: Suite, M: ClientMode> Client<'a, 'b, N, S, M> {
     /// `Mode` dependent implementation of the `blind` operation in `mode::Base`, so that the
     /// correct `context_string` is used in each mode. Reduces code duplication.
     ///
@@ -58,18 +69,10 @@ impl<'a, 'b, const N: usize, S: Suite, M: Mode> Client<'a, 'b, N, S, M> {
         inputs: [Input<'a>; N],
         rng: &mut impl RngCore,
     ) -> Result<(Client<'a, 'b, N, S, mode::Base>, [Blinded<S::Group>; N]), InvalidInput> {
-        let blinds = core::array::from_fn(|_| <S::Group as Group>::Scalar::random(rng));
-        let mut error = None;
-        let blinded_elements = core::array::from_fn(|i| {
-            let input_element: S::Group = hash_to_group::<S, M>(&[inputs[i].as_ref()]);
-            if input_element.is_identity().into() {
-                error.replace(InvalidInput);
-            }
-            Blinded(input_element * blinds[i])
-        });
-        if let Some(InvalidInput) = error {
-            return Err(InvalidInput);
-        }
+        let blinds: [<S::Group as Group>::Scalar; N] =
+            core::array::from_fn(|_| <S::Group as Group>::Scalar::random(rng));
+        let input_elements = hash_inputs::<N, S, M>(inputs)?;
+        let blinded_elements = map_array(|i| Blinded(input_elements[i] * blinds[i]));
 
         Ok((
             Client {
@@ -85,6 +88,13 @@ impl<'a, 'b, const N: usize, S: Suite, M: Mode> Client<'a, 'b, N, S, M> {
     ///
     /// Specified in [RFC 9497 Section 3.3.1](https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.1-7)
     fn finalize_impl(self, evaluated_elements: [Evaluated<S::Group>; N]) -> [Output<S::Hash>; N] {
+        let unblinded_elements = self.unblind_unchecked(evaluated_elements);
+        let inputs = self.inputs;
+        map_array(|i| finalize_hash::<S>(inputs[i], None, unblinded_elements[i]))
+    }
+
+    /// Unblind the evaluated elements, once the proof is verified.
+    fn unblind_unchecked(&self, evaluated_elements: [Evaluated<S::Group>; N]) -> [S::Group; N] {
         let inverted_blinds = if N == 1 {
             self.blinds.map(|b| b.invert().expect("blind is non-zero"))
         } else {
@@ -94,18 +104,7 @@ impl<'a, 'b, const N: usize, S: Suite, M: Mode> Client<'a, 'b, N, S, M> {
             blinds
         };
 
-        core::array::from_fn(|i| {
-            let n = evaluated_elements[i].0 * inverted_blinds[i];
-            let unblinded_element = n.to_bytes();
-
-            let mut digest = S::Hash::new();
-            digest.update((self.inputs[i].as_ref().len() as u16).to_be_bytes());
-            digest.update(self.inputs[i].as_ref());
-            digest.update((unblinded_element.as_ref().len() as u16).to_be_bytes());
-            digest.update(unblinded_element.as_ref());
-            digest.update("Finalize");
-            digest.finalize()
-        })
+        map_array(|i| evaluated_elements[i].0 * inverted_blinds[i])
     }
 }
 
@@ -175,17 +174,67 @@ impl<'a, 'b, const N: usize, S: Suite> Client<'a, 'b, N, S, mode::Verifiable> {
         evaluated_elements: [Evaluated<S::Group>; N],
         proof: Proof<<S::Group as Group>::Scalar>,
     ) -> Result<[Output<S::Hash>; N], InvalidProof> {
-        let verifying_key = self.payload.verifying_key.0;
-        if !verify_proof::<N, S, mode::Verifiable>(
+        self.verify(evaluated_elements, proof)?;
+
+        Ok(self.finalize_impl(evaluated_elements))
+    }
+
+    /// Verify the proof and unblind the evaluated elements, without hashing them into outputs.
+    pub(crate) fn unblind(
+        &self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+    ) -> Result<[S::Group; N], InvalidProof> {
+        self.verify(evaluated_elements, proof)?;
+
+        Ok(self.unblind_unchecked(evaluated_elements))
+    }
+
+    /// Verify the proof of the evaluated elements.
+    fn verify(
+        &self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+    ) -> Result<(), InvalidProof> {
+        if !verify_proof::<S, mode::Verifiable>(
             S::Group::generator(),
-            verifying_key,
-            self.payload.blinded_elements.map(|b| b.0),
-            evaluated_elements.map(|e| e.0),
+            self.payload.verifying_key.0,
+            &self.payload.blinded_elements.map(|b| b.0),
+            &evaluated_elements.map(|e| e.0),
             proof,
         ) {
             return Err(InvalidProof);
         }
 
+        Ok(())
+    }
+
+    /// Finalize the protocol, verifying the proof with the precomputed tables of `prepared`.
+    ///
+    /// This is [`finalize`](Self::finalize) for clients verifying many proofs from the same
+    /// server. The proof is rejected if `prepared` is not for the verifying key provided when
+    /// blinding.
+    #[cfg(feature = "alloc")]
+    pub fn finalize_prepared(
+        self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+        prepared: &PreparedVerifyingKey<S, mode::Verifiable>,
+    ) -> Result<[Output<S::Hash>; N], InvalidProof> {
+        let verifying_key = self.payload.verifying_key;
+        if prepared.verifying_key() != verifying_key
+            || !verify_proof_with::<S, mode::Verifiable>(
+                verifying_key.0,
+                prepared.seed(),
+                |s, c| prepared.t2(s, c),
+                &self.payload.blinded_elements.map(|b| b.0),
+                &evaluated_elements.map(|e| e.0),
+                proof,
+            )
+        {
+            return Err(InvalidProof);
+        }
+
         Ok(self.finalize_impl(evaluated_elements))
     }
 }
@@ -207,14 +256,36 @@ impl<'a, 'b, const N: usize, S: Suite> Client<'a, 'b, N, S, mode::Partial> {
         verifying_key: crate::VerifyingKey<S::Group>,
         rng: &mut impl RngCore,
     ) -> Result<(Self, [Blinded<S::Group>; N]), InvalidInput> {
-        let framed_info = [
-            b"Info".as_slice(),
-            &(info.as_ref().len() as u16).to_be_bytes(),
-            info.as_ref(),
-        ];
-        let m = hash_to_scalar::<S, mode::Partial>(&framed_info);
+        let m = info_scalar::<S>(info);
         let t = S::Group::mul_by_generator(&m);
-        let tweaked_key = t + verifying_key.0;
+        Self::blind_tweaked(inputs, info, t + verifying_key.0, rng)
+    }
+
+    /// Blinds an input, computing the tweaked key of the server with the precomputed tables of
+    /// `prepared`.
+    ///
+    /// This is [`blind`](Self::blind) for clients querying the same server many times.
+    #[cfg(feature = "alloc")]
+    #[allow(clippy::type_complexity)]
+    pub fn blind_prepared(
+        inputs: [Input<'a>; N],
+        info: Input<'b>,
+        prepared: &PreparedVerifyingKey<S, mode::Partial>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, [Blinded<S::Group>; N]), InvalidInput> {
+        let tweaked_key = prepared.tweaked_key(&info_scalar::<S>(info));
+        Self::blind_tweaked(inputs, info, tweaked_key, rng)
+    }
+
+    /// Code shared between [`Client::blind`] and [`Client::blind_prepared`] once the tweaked key
+    /// is computed.
+    #[allow(clippy::type_complexity)]
+    fn blind_tweaked(
+        inputs: [Input<'a>; N],
+        info: Input<'b>,
+        tweaked_key: S::Group,
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, [Blinded<S::Group>; N]), InvalidInput> {
         if tweaked_key.is_identity().into() {
             return Err(InvalidInput);
         }
@@ -246,40 +317,65 @@ impl<'a, 'b, const N: usize, S: Suite> Client<'a, 'b, N, S, mode::Partial> {
         evaluated_elements: [Evaluated<S::Group>; N],
         proof: Proof<<S::Group as Group>::Scalar>,
     ) -> Result<[Output<S::Hash>; N], InvalidProof> {
+        let unblinded_elements = self.unblind(evaluated_elements, proof)?;
+
+        Ok(self.finalize_unblinded(unblinded_elements))
+    }
+
+    /// Finalize the protocol, verifying the proof with the precomputed tables of `prepared`.
+    ///
+    /// This is [`finalize`](Self::finalize) for clients verifying many proofs from the same
+    /// server. The proof is rejected if `prepared` is not for the verifying key provided when
+    /// blinding.
+    #[cfg(feature = "alloc")]
+    pub fn finalize_prepared(
+        self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+        prepared: &PreparedVerifyingKey<S, mode::Partial>,
+    ) -> Result<[Output<S::Hash>; N], InvalidProof> {
+        let tweaked_key = self.payload.verifying_key.0;
+        let m = info_scalar::<S>(self.payload.info);
+        if prepared.tweaked_key(&m) != tweaked_key
+            || !verify_proof_with::<S, mode::Partial>(
+                tweaked_key,
+                &composite_seed::<S, mode::Partial>(tweaked_key),
+                |s, c| prepared.t2(m, s, c),
+                &evaluated_elements.map(|e| e.0),
+                &self.payload.blinded_elements.map(|b| b.0),
+                proof,
+            )
+        {
+            return Err(InvalidProof);
+        }
+
+        Ok(self.finalize_unblinded(self.unblind_unchecked(evaluated_elements)))
+    }
+
+    /// Verify the proof and unblind the evaluated elements, without hashing them into outputs.
+    pub(crate) fn unblind(
+        &self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+    ) -> Result<[S::Group; N], InvalidProof> {
         let verifying_key = self.payload.verifying_key.0;
-        if !verify_proof::<N, S, mode::Partial>(
+        if !verify_proof::<S, mode::Partial>(
             S::Group::generator(),
             verifying_key,
-            evaluated_elements.map(|e| e.0),
-            self.payload.blinded_elements.map(|b| b.0),
+            &evaluated_elements.map(|e| e.0),
+            &self.payload.blinded_elements.map(|b| b.0),
             proof,
         ) {
             return Err(InvalidProof);
         }
 
-        let inverted_blinds = if N == 1 {
-            self.blinds.map(|b| b.invert().expect("blind is non-zero"))
-        } else {
-            let mut blinds = self.blinds;
-            let mut scratch = [<S::Group as Group>::Scalar::ONE; N];
-            group::ff::BatchInverter::invert_with_external_scratch(&mut blinds, &mut scratch);
-            blinds
-        };
+        Ok(self.unblind_unchecked(evaluated_elements))
+    }
 
-        Ok(core::array::from_fn(|i| {
-            let n = evaluated_elements[i].0 * inverted_blinds[i];
-            let unblinded_element = n.to_bytes();
-
-            let mut digest = S::Hash::new();
-            digest.update((self.inputs[i].as_ref().len() as u16).to_be_bytes());
-            digest.update(self.inputs[i].as_ref());
-            digest.update((self.payload.info.as_ref().len() as u16).to_be_bytes());
-            digest.update(self.payload.info.as_ref());
-            digest.update((unblinded_element.as_ref().len() as u16).to_be_bytes());
-            digest.update(unblinded_element.as_ref());
-            digest.update("Finalize");
-            digest.finalize()
-        }))
+    /// Hash the unblinded elements into outputs.
+    fn finalize_unblinded(&self, unblinded_elements: [S::Group; N]) -> [Output<S::Hash>; N] {
+        let (inputs, info) = (self.inputs, self.payload.info);
+        map_array(|i| finalize_hash::<S>(inputs[i], Some(info), unblinded_elements[i]))
     }
 }
 
@@ -0,0 +1,161 @@
+//! Server-aided convergent encryption keys, in the style of [DupLESS].
+//!
+//! Deduplicated encrypted storage requires that identical files are encrypted under identical
+//! keys. Deriving the key from the file content alone (message-locked encryption) lets anyone
+//! brute force predictable files offline. Here, the key is instead the output of the OPRF over the
+//! [`ContentHash`] of the file, so that guessing a file requires an online interaction with the key
+//! server for every guess, and the key server learns nothing about the files.
+//!
+//! The key server runs a [`Server`] in the [`Verifiable`] mode, so that clients detect a key server
+//! that evaluates with the wrong key, which would silently break deduplication.
+//!
+//! [DupLESS]: https://eprint.iacr.org/2013/429
+//! [`Server`]: crate::server::Server
+
+use digest::{Digest, Output};
+use group::Group;
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    expand,
+    mode::Verifiable,
+};
+
+/// The hash of the content of a file.
+///
+/// This is the input of the OPRF, as the content of a file can be arbitrarily long.
+pub struct ContentHash<S: Suite>(pub Output<S::Hash>);
+
+impl<S: Suite> ContentHash<S> {
+    /// Hash the content of a file.
+    ///
+    /// For large files, use [`ContentHasher`] instead.
+    pub fn new(content: &[u8]) -> Self {
+        let mut hasher = ContentHasher::new();
+        hasher.update(content);
+        hasher.finalize()
+    }
+
+    fn input(&self) -> Input<'_> {
+        Input(&self.0)
+    }
+}
+
+impl<S: Suite> Clone for ContentHash<S> {
+    fn clone(&self) -> Self {
+        ContentHash(self.0.clone())
+    }
+}
+
+impl<S: Suite> core::fmt::Debug for ContentHash<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ContentHash").field(&self.0).finish()
+    }
+}
+
+impl<S: Suite> PartialEq for ContentHash<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<S: Suite> Eq for ContentHash<S> {}
+
+/// Incremental computation of a [`ContentHash`].
+pub struct ContentHasher<S: Suite>(S::Hash);
+
+impl<S: Suite> ContentHasher<S> {
+    /// Start hashing the content of a file.
+    pub fn new() -> Self {
+        ContentHasher(S::Hash::new_with_prefix(b"ContentHash"))
+    }
+
+    /// Hash the next part of the content.
+    pub fn update(&mut self, content: &[u8]) {
+        self.0.update(content);
+    }
+
+    /// Finish hashing the content.
+    pub fn finalize(self) -> ContentHash<S> {
+        ContentHash(self.0.finalize())
+    }
+}
+
+impl<S: Suite> Default for ContentHasher<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An encryption key for a file.
+///
+/// The key is zeroized on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FileKey<const K: usize>(pub [u8; K]);
+
+impl<const K: usize> FileKey<K> {
+    /// Derive a file key from the output of the OPRF.
+    pub fn from_output<S: Suite>(output: &Output<S::Hash>) -> Self {
+        let mut key = [0; K];
+        expand::<S>(output, b"FileKey", &mut key);
+        FileKey(key)
+    }
+}
+
+impl<const K: usize> zeroize::Zeroize for FileKey<K> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const K: usize> Drop for FileKey<K> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl<const K: usize> zeroize::ZeroizeOnDrop for FileKey<K> {}
+
+impl<const K: usize> core::fmt::Debug for FileKey<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("FileKey(..)")
+    }
+}
+
+/// A request for the keys of `N` files to the key server.
+pub struct KeyRequest<'a, const N: usize, S: Suite> {
+    client: Client<'a, 'static, N, S, Verifiable>,
+}
+
+impl<'a, const N: usize, S: Suite> KeyRequest<'a, N, S> {
+    /// Blind the content hashes of the files.
+    ///
+    /// The blinded elements are sent to the key server, which evaluates them with
+    /// [`Server<_, Verifiable>::evaluate`](crate::server::Server#impl-Server<S,+Verifiable>).
+    #[allow(clippy::type_complexity)]
+    pub fn new(
+        content_hashes: &'a [ContentHash<S>; N],
+        verifying_key: VerifyingKey<S::Group>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, [Blinded<S::Group>; N]), InvalidInput> {
+        let inputs = content_hashes.each_ref().map(ContentHash::input);
+        let (client, blinded_elements) =
+            Client::<_, S, Verifiable>::blind(inputs, verifying_key, rng)?;
+        Ok((KeyRequest { client }, blinded_elements))
+    }
+
+    /// Verify the evaluation of the key server, and derive the keys of the files.
+    ///
+    /// Fails if the key server did not evaluate the blinded elements with the key matching the
+    /// [`VerifyingKey`] provided to [`KeyRequest::new`].
+    pub fn finish<const K: usize>(
+        self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+    ) -> Result<[FileKey<K>; N], InvalidProof> {
+        let outputs = self.client.finalize(evaluated_elements, proof)?;
+        Ok(outputs.map(|output| FileKey::from_output::<S>(&output)))
+    }
+}
@@ -0,0 +1,522 @@
+//! Issuer key directories and client-side key pinning.
+//!
+//! In deployments like Privacy Pass, an issuer publishes its verifying keys in a directory, and
+//! clients must make sure they see the same keys as everyone else, or the issuer could tell them
+//! apart by the key it gives to each.
+//!
+//! - A [`KeyDirectory`] lists the keys of an issuer for a suite and mode, each with its [`KeyId`],
+//!   its window of validity `[not_before, not_after)`, and the static [proof of possession] of its
+//!   secret key. It is encoded with [`KeyDirectory::to_bytes`], or as JSON with the `json` feature.
+//!   The proofs are deterministic, so the same keys always give the same encoding, and clients can
+//!   compare the [`KeyDirectory::digest`] of the directory they got out of band.
+//! - A [`TrustStore`] pins the keys of a directory on the client, after checking their proofs of
+//!   possession. It selects the pinned key to pass to [`Client::blind`], detects when a later
+//!   directory changes a pinned key before it expires, and rejects directories with more
+//!   concurrently valid keys than expected, as handing a new key to each client is how an issuer
+//!   would tell them apart.
+//!
+//! [proof of possession]: crate::possession
+//! [`Client::blind`]: crate::client::Client#method.blind
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use digest::{Digest, Output};
+use group::{Group, GroupEncoding, ff::PrimeField};
+
+use crate::{
+    Input, InvalidEncoding, Proof, Suite, VerifyingKey,
+    client::InvalidProof,
+    encoding::{Reader, put},
+    mode::Mode,
+    possession::verify_possession,
+    rotation::{InvalidWindow, KeyId, KeyManager},
+    server::Server,
+};
+
+const MAGIC: &[u8; 8] = b"OPRF-DIR";
+const VERSION: u8 = 1;
+
+/// A key of a [`KeyDirectory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectoryEntry<G: Group> {
+    /// The identifier of the key.
+    pub id: KeyId,
+    /// The verifying key.
+    pub verifying_key: VerifyingKey<G>,
+    /// The time from which the key is valid.
+    pub not_before: u64,
+    /// The time from which the key is not valid anymore.
+    pub not_after: u64,
+    /// The static proof of possession of the secret key, from
+    /// [`Server::static_possession_proof`].
+    pub proof: Proof<G::Scalar>,
+}
+
+/// The keys of an issuer, published to its clients.
+pub struct KeyDirectory<S: Suite, M: Mode> {
+    entries: Vec<DirectoryEntry<S::Group>>,
+    _marker: core::marker::PhantomData<M>,
+}
+
+impl<S: Suite, M: Mode> Clone for KeyDirectory<S, M> {
+    fn clone(&self) -> Self {
+        KeyDirectory {
+            entries: self.entries.clone(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<S: Suite, M: Mode> core::fmt::Debug for KeyDirectory<S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KeyDirectory")
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl<S: Suite, M: Mode> PartialEq for KeyDirectory<S, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<S: Suite, M: Mode> Eq for KeyDirectory<S, M> {}
+
+impl<S: Suite, M: Mode> Default for KeyDirectory<S, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Suite, M: Mode> KeyDirectory<S, M> {
+    /// Create an empty directory.
+    pub fn new() -> Self {
+        KeyDirectory {
+            entries: Vec::new(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Add the key of the `server`, valid from `not_before` until `not_after`, excluded.
+    ///
+    /// If the key was already added, its window is replaced.
+    pub fn insert(
+        &mut self,
+        server: &Server<S, M>,
+        not_before: u64,
+        not_after: u64,
+    ) -> Result<KeyId, InvalidWindow> {
+        if not_after <= not_before {
+            return Err(InvalidWindow);
+        }
+        let verifying_key = server.verifying_key();
+        let id = KeyId::new::<S>(verifying_key);
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.push(DirectoryEntry {
+            id,
+            verifying_key,
+            not_before,
+            not_after,
+            proof: server.static_possession_proof(),
+        });
+        Ok(id)
+    }
+
+    /// The keys of the directory, in the order they were added.
+    pub fn entries(&self) -> &[DirectoryEntry<S::Group>] {
+        &self.entries
+    }
+
+    /// The key `id`.
+    pub fn get(&self, id: &KeyId) -> Option<&DirectoryEntry<S::Group>> {
+        self.entries.iter().find(|entry| entry.id == *id)
+    }
+
+    /// Encode the directory.
+    ///
+    /// The encoding is `"OPRF-DIR" || I2OSP(1, 1) || I2OSP(mode, 1) || I2OSP(len(suite), 2) ||
+    /// suite || I2OSP(n, 2)`, followed by each of the `n` keys encoded as `id ||
+    /// I2OSP(len(pkS), 2) || pkS || I2OSP(not_before, 8) || I2OSP(not_after, 8) ||
+    /// I2OSP(len(c), 2) || c || I2OSP(len(s), 2) || s`, where `(c, s)` is the proof of possession.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the directory has more than `u16::MAX` keys.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(M::IDENTIFIER.get());
+        put(&mut bytes, S::IDENTIFIER);
+        let len = u16::try_from(self.entries.len()).expect("at most 2^16 - 1 keys");
+        bytes.extend_from_slice(&len.to_be_bytes());
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.id.0);
+            put(&mut bytes, entry.verifying_key.0.to_bytes().as_ref());
+            bytes.extend_from_slice(&entry.not_before.to_be_bytes());
+            bytes.extend_from_slice(&entry.not_after.to_be_bytes());
+            put(&mut bytes, entry.proof.c.to_repr().as_ref());
+            put(&mut bytes, entry.proof.s.to_repr().as_ref());
+        }
+        bytes
+    }
+
+    /// The digest of the encoding of the directory, with the hash function of the suite.
+    ///
+    /// Clients that got the same directory get the same digest, which they can compare out of band
+    /// to make sure the issuer did not give them different keys.
+    pub fn digest(&self) -> Output<S::Hash> {
+        S::Hash::digest(self.to_bytes())
+    }
+
+    /// Decode a directory encoded with [`to_bytes`](Self::to_bytes).
+    ///
+    /// The suite and mode of the directory must match. The proofs of possession are only checked
+    /// by [`TrustStore::update`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InvalidEncoding> {
+        let mut reader = Reader::new(bytes);
+        if reader.bytes(MAGIC.len())? != MAGIC
+            || reader.u8()? != VERSION
+            || reader.u8()? != M::IDENTIFIER.get()
+            || reader.field()? != S::IDENTIFIER
+        {
+            return Err(InvalidEncoding);
+        }
+
+        let mut directory = Self::new();
+        for _ in 0..reader.u16()? {
+            let id = KeyId(reader.bytes(32)?.try_into().expect("32 bytes were read"));
+            let verifying_key = VerifyingKey(reader.element()?);
+            let not_before = reader.u64()?;
+            let not_after = reader.u64()?;
+            let proof = Proof {
+                c: reader.scalar()?,
+                s: reader.scalar()?,
+            };
+            directory.push(DirectoryEntry {
+                id,
+                verifying_key,
+                not_before,
+                not_after,
+                proof,
+            })?;
+        }
+        reader.finish()?;
+
+        Ok(directory)
+    }
+
+    /// Add a decoded entry, checking that it is consistent and not a duplicate.
+    fn push(&mut self, entry: DirectoryEntry<S::Group>) -> Result<(), InvalidEncoding> {
+        if entry.id != KeyId::new::<S>(entry.verifying_key)
+            || entry.not_after <= entry.not_before
+            || self.get(&entry.id).is_some()
+        {
+            return Err(InvalidEncoding);
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
+impl<S: Suite, M: Mode> KeyDirectory<S, M> {
+    /// Encode the directory as JSON.
+    ///
+    /// The directory is encoded as
+    /// `{"keys":[...],"mode":mode,"suite":suite,"version":1}`, with each key encoded as
+    /// `{"id":id,"key":pkS,"not_after":not_after,"not_before":not_before,"proof":c || s}`, where
+    /// binary fields are encoded in base64url without padding.
+    ///
+    /// Fails if the identifier of the suite is not valid UTF-8.
+    pub fn to_json(&self) -> Result<alloc::string::String, crate::UnsupportedSuite> {
+        use base64ct::{Base64UrlUnpadded, Encoding};
+        use serde_json::{Map, Value};
+
+        let suite = core::str::from_utf8(S::IDENTIFIER).map_err(|_| crate::UnsupportedSuite)?;
+        let keys = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut proof = Vec::new();
+                proof.extend_from_slice(entry.proof.c.to_repr().as_ref());
+                proof.extend_from_slice(entry.proof.s.to_repr().as_ref());
+
+                let mut key = Map::new();
+                key.insert(
+                    "id".into(),
+                    Base64UrlUnpadded::encode_string(&entry.id.0).into(),
+                );
+                key.insert(
+                    "key".into(),
+                    Base64UrlUnpadded::encode_string(entry.verifying_key.0.to_bytes().as_ref())
+                        .into(),
+                );
+                key.insert("not_before".into(), entry.not_before.into());
+                key.insert("not_after".into(), entry.not_after.into());
+                key.insert(
+                    "proof".into(),
+                    Base64UrlUnpadded::encode_string(&proof).into(),
+                );
+                Value::Object(key)
+            })
+            .collect();
+
+        let mut directory = Map::new();
+        directory.insert("version".into(), VERSION.into());
+        directory.insert("suite".into(), suite.into());
+        directory.insert("mode".into(), M::IDENTIFIER.get().into());
+        directory.insert("keys".into(), Value::Array(keys));
+        Ok(serde_json::to_string(&Value::Object(directory))
+            .expect("the directory is JSON encodable"))
+    }
+
+    /// Decode a directory encoded with [`to_json`](Self::to_json).
+    ///
+    /// The suite and mode of the directory must match. The proofs of possession are only checked
+    /// by [`TrustStore::update`].
+    pub fn from_json(json: &str) -> Result<Self, InvalidEncoding> {
+        use base64ct::{Base64UrlUnpadded, Encoding};
+        use serde_json::Value;
+
+        use crate::encoding::{decode_element, decode_scalar};
+
+        let bytes = |value: Option<&Value>| {
+            let value = value.and_then(Value::as_str).ok_or(InvalidEncoding)?;
+            Base64UrlUnpadded::decode_vec(value).map_err(|_| InvalidEncoding)
+        };
+        let Ok(Value::Object(directory)) = serde_json::from_str(json) else {
+            return Err(InvalidEncoding);
+        };
+        if directory.get("version").and_then(Value::as_u64) != Some(VERSION.into())
+            || directory.get("mode").and_then(Value::as_u64) != Some(M::IDENTIFIER.get().into())
+            || directory
+                .get("suite")
+                .and_then(Value::as_str)
+                .map(str::as_bytes)
+                != Some(S::IDENTIFIER)
+        {
+            return Err(InvalidEncoding);
+        }
+
+        let mut decoded = Self::new();
+        let keys = directory
+            .get("keys")
+            .and_then(Value::as_array)
+            .ok_or(InvalidEncoding)?;
+        for key in keys {
+            let proof = bytes(key.get("proof"))?;
+            if proof.len() % 2 != 0 {
+                return Err(InvalidEncoding);
+            }
+            let (c, s) = proof.split_at(proof.len() / 2);
+            decoded.push(DirectoryEntry {
+                id: KeyId(
+                    bytes(key.get("id"))?
+                        .try_into()
+                        .map_err(|_| InvalidEncoding)?,
+                ),
+                verifying_key: VerifyingKey(decode_element(&bytes(key.get("key"))?)?),
+                not_before: key
+                    .get("not_before")
+                    .and_then(Value::as_u64)
+                    .ok_or(InvalidEncoding)?,
+                not_after: key
+                    .get("not_after")
+                    .and_then(Value::as_u64)
+                    .ok_or(InvalidEncoding)?,
+                proof: Proof {
+                    c: decode_scalar(c)?,
+                    s: decode_scalar(s)?,
+                },
+            })?;
+        }
+
+        Ok(decoded)
+    }
+}
+
+impl<S: Suite, M: Mode> KeyManager<S, M> {
+    /// The directory of the keys of the manager, activated or not, to publish to clients.
+    ///
+    /// The keys are listed by [`KeyId`], so the same keys always give the same directory.
+    pub fn directory(&self) -> KeyDirectory<S, M> {
+        let mut directory = KeyDirectory::new();
+        for (_, server, activation, expiry) in self.keys() {
+            directory
+                .insert(server, activation, expiry)
+                .expect("the windows of the manager are valid");
+        }
+        directory
+    }
+}
+
+/// A pinned key of a [`TrustStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pin<G> {
+    verifying_key: VerifyingKey<G>,
+    not_before: u64,
+    not_after: u64,
+}
+
+/// The keys of an issuer pinned by a client.
+pub struct TrustStore<S: Suite, M: Mode> {
+    pins: BTreeMap<KeyId, Pin<S::Group>>,
+    max_valid_keys: usize,
+    _marker: core::marker::PhantomData<M>,
+}
+
+impl<S: Suite, M: Mode> Clone for TrustStore<S, M> {
+    fn clone(&self) -> Self {
+        TrustStore {
+            pins: self.pins.clone(),
+            max_valid_keys: self.max_valid_keys,
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<S: Suite, M: Mode> core::fmt::Debug for TrustStore<S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TrustStore")
+            .field("pins", &self.pins)
+            .field("max_valid_keys", &self.max_valid_keys)
+            .finish()
+    }
+}
+
+impl<S: Suite, M: Mode> Default for TrustStore<S, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Suite, M: Mode> TrustStore<S, M> {
+    /// Create a store without any pinned key, accepting at most 2 concurrently valid keys: the
+    /// current key, and the next one during a rotation.
+    pub fn new() -> Self {
+        TrustStore {
+            pins: BTreeMap::new(),
+            max_valid_keys: 2,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Accept directories with at most `max_valid_keys` keys valid at the same time.
+    pub fn with_max_valid_keys(mut self, max_valid_keys: usize) -> Self {
+        self.max_valid_keys = max_valid_keys;
+        self
+    }
+
+    /// Pin the keys of the `directory` fetched at time `now`.
+    ///
+    /// Every key must come with a valid proof of possession. The keys pinned by earlier updates
+    /// must either be in the `directory` with the same window, or have expired at time `now`:
+    /// otherwise the issuer changed a key that clients may still be using, and the update fails
+    /// with [`TrustError::KeyChanged`]. From time `now` on, no more keys than the maximum of the
+    /// store may be valid at the same time, or the update fails with [`TrustError::TooManyKeys`].
+    /// When an update fails, the store is not modified.
+    pub fn update(&mut self, directory: &KeyDirectory<S, M>, now: u64) -> Result<(), TrustError> {
+        for entry in directory.entries() {
+            verify_possession::<S, M>(entry.verifying_key, Input(&[]), entry.proof)?;
+        }
+
+        // The number of valid keys only increases at the start of a window, or at `now`.
+        let entries = directory.entries();
+        let too_many = entries
+            .iter()
+            .filter(|entry| now < entry.not_after)
+            .any(|entry| {
+                let time = entry.not_before.max(now);
+                entries
+                    .iter()
+                    .filter(|other| other.not_before <= time && time < other.not_after)
+                    .count()
+                    > self.max_valid_keys
+            });
+        if too_many {
+            return Err(TrustError::TooManyKeys);
+        }
+
+        for (id, pin) in &self.pins {
+            let unchanged = match directory.get(id) {
+                Some(entry) => {
+                    entry.not_before == pin.not_before && entry.not_after == pin.not_after
+                }
+                None => pin.not_after <= now,
+            };
+            if !unchanged {
+                return Err(TrustError::KeyChanged(*id));
+            }
+        }
+
+        self.pins = directory
+            .entries()
+            .iter()
+            .map(|entry| {
+                let pin = Pin {
+                    verifying_key: entry.verifying_key,
+                    not_before: entry.not_before,
+                    not_after: entry.not_after,
+                };
+                (entry.id, pin)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// The pinned key `id`, if it is valid at time `now`.
+    pub fn verifying_key(&self, id: &KeyId, now: u64) -> Option<VerifyingKey<S::Group>> {
+        self.pins
+            .get(id)
+            .filter(|pin| pin.not_before <= now && now < pin.not_after)
+            .map(|pin| pin.verifying_key)
+    }
+
+    /// The key to blind with at time `now`: the most recently activated pinned key that has not
+    /// expired.
+    pub fn current(&self, now: u64) -> Option<(KeyId, VerifyingKey<S::Group>)> {
+        self.pins
+            .iter()
+            .filter(|(_, pin)| pin.not_before <= now && now < pin.not_after)
+            .max_by_key(|(_, pin)| pin.not_before)
+            .map(|(id, pin)| (*id, pin.verifying_key))
+    }
+
+    /// The identifiers of the pinned keys, valid or not.
+    pub fn ids(&self) -> impl Iterator<Item = &KeyId> {
+        self.pins.keys()
+    }
+}
+
+/// Error returned when updating a [`TrustStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TrustError {
+    /// The proof of possession of a key is invalid.
+    InvalidProof,
+    /// A pinned key was changed or removed before it expired.
+    KeyChanged(KeyId),
+    /// More keys are valid at the same time than the maximum of the store.
+    TooManyKeys,
+}
+
+impl From<InvalidProof> for TrustError {
+    fn from(_: InvalidProof) -> Self {
+        TrustError::InvalidProof
+    }
+}
+
+impl core::fmt::Display for TrustError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TrustError::InvalidProof => write!(f, "the proof of possession of a key is invalid"),
+            TrustError::KeyChanged(_) => {
+                write!(f, "a pinned key was changed or removed before it expired")
+            }
+            TrustError::TooManyKeys => write!(f, "too many keys are valid at the same time"),
+        }
+    }
+}
+
+impl core::error::Error for TrustError {}
@@ -0,0 +1,592 @@
+//! Suites and modes selected at runtime.
+//!
+//! [`Client`] and [`Server`] are generic over their [`Suite`] and mode, which is not practical for
+//! services negotiating them per request. This module provides a dynamic layer over them:
+//!
+//! - [`AnySuite`] and [`AnyMode`] name the suites and modes of RFC 9497, and are looked up by their
+//!   identifiers.
+//! - The crate does not implement any suite, so the application registers its implementations in
+//!   a [`Registry`], which dispatches to them by [`AnySuite`].
+//! - [`DynServer`] and [`DynClient`] run the protocol on serialized elements, for a single input.
+//!   The evaluation of the server is serialized as the evaluated element, followed by the
+//!   serialized [`Proof`] in the [`Verifiable`] and [`Partial`] modes.
+//!
+//! The outputs are the same as the ones of the static implementations.
+
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+use group::{Group, GroupEncoding, ff::Field, ff::PrimeField};
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, InvalidEncoding, Proof, Suite, UnsupportedSuite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    encoding::{decode_element, decode_scalar},
+    mode::{Base, Mode, Partial, Verifiable},
+    server::{SecretKey, Server, UndefinedInverse},
+};
+
+/// A ciphersuite of RFC 9497.
+///
+/// Defined in [RFC 9497 Section 4](https://www.rfc-editor.org/rfc/rfc9497.html#name-ciphersuites).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnySuite {
+    /// `ristretto255-SHA512`.
+    Ristretto255Sha512,
+    /// `decaf448-SHAKE256`.
+    Decaf448Shake256,
+    /// `P256-SHA256`.
+    P256Sha256,
+    /// `P384-SHA384`.
+    P384Sha384,
+    /// `P521-SHA512`.
+    P521Sha512,
+}
+
+impl AnySuite {
+    /// Every suite of RFC 9497.
+    pub const ALL: [AnySuite; 5] = [
+        AnySuite::Ristretto255Sha512,
+        AnySuite::Decaf448Shake256,
+        AnySuite::P256Sha256,
+        AnySuite::P384Sha384,
+        AnySuite::P521Sha512,
+    ];
+
+    /// The identifier of the suite, as in [`Suite::IDENTIFIER`].
+    pub const fn identifier(self) -> &'static [u8] {
+        match self {
+            AnySuite::Ristretto255Sha512 => b"ristretto255-SHA512",
+            AnySuite::Decaf448Shake256 => b"decaf448-SHAKE256",
+            AnySuite::P256Sha256 => b"P256-SHA256",
+            AnySuite::P384Sha384 => b"P384-SHA384",
+            AnySuite::P521Sha512 => b"P521-SHA512",
+        }
+    }
+
+    /// The suite with the `identifier`.
+    pub fn from_identifier(identifier: &[u8]) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|suite| suite.identifier() == identifier)
+    }
+}
+
+/// A mode of RFC 9497.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnyMode {
+    /// The [`Base`] mode.
+    Base,
+    /// The [`Verifiable`] mode.
+    Verifiable,
+    /// The [`Partial`] mode.
+    Partial,
+}
+
+impl AnyMode {
+    /// The identifier of the mode, used in the context string.
+    pub const fn identifier(self) -> u8 {
+        match self {
+            AnyMode::Base => Base::IDENTIFIER.get(),
+            AnyMode::Verifiable => Verifiable::IDENTIFIER.get(),
+            AnyMode::Partial => Partial::IDENTIFIER.get(),
+        }
+    }
+
+    /// The mode with the `identifier`.
+    pub fn from_identifier(identifier: u8) -> Option<Self> {
+        [AnyMode::Base, AnyMode::Verifiable, AnyMode::Partial]
+            .into_iter()
+            .find(|mode| mode.identifier() == identifier)
+    }
+}
+
+type ServerFn = fn(AnyMode, &[u8]) -> Result<Box<dyn ErasedServer>, DynError>;
+type RandomServerFn = fn(AnyMode, &mut dyn RngCore) -> Box<dyn ErasedServer>;
+type BlindFn = for<'a> fn(
+    AnyMode,
+    Input<'a>,
+    Option<Input<'a>>,
+    Option<&[u8]>,
+    &mut dyn RngCore,
+) -> Result<(Box<dyn ErasedClient + 'a>, Vec<u8>), DynError>;
+
+/// The monomorphized functions of a registered suite.
+#[derive(Clone, Copy)]
+struct SuiteFns {
+    server: ServerFn,
+    random_server: RandomServerFn,
+    blind: BlindFn,
+}
+
+/// The suites implemented by the application.
+#[derive(Clone, Default)]
+pub struct Registry {
+    suites: BTreeMap<AnySuite, SuiteFns>,
+}
+
+impl core::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Registry")
+            .field("suites", &self.suites.keys())
+            .finish()
+    }
+}
+
+impl Registry {
+    /// Create a registry without any suite.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the implementation `S` of a suite, replacing any previous implementation.
+    ///
+    /// Fails if the [`Suite::IDENTIFIER`] of `S` is not the one of a suite of RFC 9497, or if `S`
+    /// has a [`Suite::DOMAIN`].
+    pub fn register<S: Suite + 'static>(&mut self) -> Result<AnySuite, UnsupportedSuite> {
+        if !S::DOMAIN.is_empty() {
+            return Err(UnsupportedSuite);
+        }
+        let suite = AnySuite::from_identifier(S::IDENTIFIER).ok_or(UnsupportedSuite)?;
+        self.suites.insert(
+            suite,
+            SuiteFns {
+                server: server::<S>,
+                random_server: random_server::<S>,
+                blind: blind::<S>,
+            },
+        );
+        Ok(suite)
+    }
+
+    /// The registered suites.
+    pub fn suites(&self) -> impl Iterator<Item = AnySuite> {
+        self.suites.keys().copied()
+    }
+
+    /// Initialize a server from its serialized secret key.
+    pub fn server(
+        &self,
+        suite: AnySuite,
+        mode: AnyMode,
+        secret_key: &[u8],
+    ) -> Result<DynServer, DynError> {
+        let server = (self.fns(suite)?.server)(mode, secret_key)?;
+        Ok(DynServer {
+            suite,
+            mode,
+            server,
+        })
+    }
+
+    /// Initialize a server with a random secret key.
+    pub fn random_server(
+        &self,
+        suite: AnySuite,
+        mode: AnyMode,
+        rng: &mut dyn RngCore,
+    ) -> Result<DynServer, DynError> {
+        let server = (self.fns(suite)?.random_server)(mode, rng);
+        Ok(DynServer {
+            suite,
+            mode,
+            server,
+        })
+    }
+
+    /// Blind the `input`, returning the client and the serialized blinded element.
+    ///
+    /// The serialized `verifying_key` of the server must be provided in the [`Verifiable`] and
+    /// [`Partial`] modes, and the shared `info` in the [`Partial`] mode only.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blind<'a>(
+        &self,
+        suite: AnySuite,
+        mode: AnyMode,
+        input: Input<'a>,
+        info: Option<Input<'a>>,
+        verifying_key: Option<&[u8]>,
+        rng: &mut dyn RngCore,
+    ) -> Result<(DynClient<'a>, Vec<u8>), DynError> {
+        let (client, blinded_element) =
+            (self.fns(suite)?.blind)(mode, input, info, verifying_key, rng)?;
+        Ok((
+            DynClient {
+                suite,
+                mode,
+                client,
+            },
+            blinded_element,
+        ))
+    }
+
+    fn fns(&self, suite: AnySuite) -> Result<&SuiteFns, DynError> {
+        self.suites.get(&suite).ok_or(DynError::UnknownSuite)
+    }
+}
+
+/// A [`Server`] of a suite and mode selected at runtime.
+pub struct DynServer {
+    suite: AnySuite,
+    mode: AnyMode,
+    server: Box<dyn ErasedServer>,
+}
+
+impl core::fmt::Debug for DynServer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DynServer")
+            .field("suite", &self.suite)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DynServer {
+    /// The suite of the server.
+    pub fn suite(&self) -> AnySuite {
+        self.suite
+    }
+
+    /// The mode of the server.
+    pub fn mode(&self) -> AnyMode {
+        self.mode
+    }
+
+    /// The serialized verifying key of the server.
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.server.verifying_key()
+    }
+
+    /// Evaluate the serialized blinded element, returning the serialized evaluation.
+    ///
+    /// The shared `info` must be provided in the [`Partial`] mode only.
+    pub fn evaluate(
+        &self,
+        blinded_element: &[u8],
+        info: Option<Input<'_>>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, DynError> {
+        self.server.evaluate(blinded_element, info, rng)
+    }
+}
+
+/// A [`Client`] of a suite and mode selected at runtime, created by [`Registry::blind`].
+pub struct DynClient<'a> {
+    suite: AnySuite,
+    mode: AnyMode,
+    client: Box<dyn ErasedClient + 'a>,
+}
+
+impl core::fmt::Debug for DynClient<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DynClient")
+            .field("suite", &self.suite)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DynClient<'_> {
+    /// The suite of the client.
+    pub fn suite(&self) -> AnySuite {
+        self.suite
+    }
+
+    /// The mode of the client.
+    pub fn mode(&self) -> AnyMode {
+        self.mode
+    }
+
+    /// Finalize the protocol with the serialized evaluation of the server, returning the output.
+    pub fn finalize(self, evaluation: &[u8]) -> Result<Vec<u8>, DynError> {
+        self.client.finalize(evaluation)
+    }
+}
+
+/// A [`Server`] with its suite and mode erased.
+trait ErasedServer {
+    fn verifying_key(&self) -> Vec<u8>;
+
+    fn evaluate(
+        &self,
+        blinded_element: &[u8],
+        info: Option<Input<'_>>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, DynError>;
+}
+
+/// A [`Client`] with its suite and mode erased.
+trait ErasedClient {
+    fn finalize(self: Box<Self>, evaluation: &[u8]) -> Result<Vec<u8>, DynError>;
+}
+
+fn server<S: Suite + 'static>(
+    mode: AnyMode,
+    secret_key: &[u8],
+) -> Result<Box<dyn ErasedServer>, DynError> {
+    let key = SecretKey::new(decode_scalar::<<S::Group as Group>::Scalar>(secret_key)?);
+    if key.expose_secret().is_zero().into() {
+        return Err(InvalidEncoding.into());
+    }
+    Ok(match mode {
+        AnyMode::Base => Box::new(Server::<S, Base>::from_secret_key(key)),
+        AnyMode::Verifiable => Box::new(Server::<S, Verifiable>::from_secret_key(key)),
+        AnyMode::Partial => Box::new(Server::<S, Partial>::from_secret_key(key)),
+    })
+}
+
+fn random_server<S: Suite + 'static>(
+    mode: AnyMode,
+    mut rng: &mut dyn RngCore,
+) -> Box<dyn ErasedServer> {
+    match mode {
+        AnyMode::Base => Box::new(Server::<S, Base>::random(&mut rng)),
+        AnyMode::Verifiable => Box::new(Server::<S, Verifiable>::random(&mut rng)),
+        AnyMode::Partial => Box::new(Server::<S, Partial>::random(&mut rng)),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn blind<'a, S: Suite + 'static>(
+    mode: AnyMode,
+    input: Input<'a>,
+    info: Option<Input<'a>>,
+    verifying_key: Option<&[u8]>,
+    mut rng: &mut dyn RngCore,
+) -> Result<(Box<dyn ErasedClient + 'a>, Vec<u8>), DynError> {
+    let verifying_key = verifying_key
+        .map(|key| decode_element(key).map(VerifyingKey))
+        .transpose()?;
+
+    let (client, [blinded_element]): (Box<dyn ErasedClient + 'a>, _) =
+        match (mode, info, verifying_key) {
+            (AnyMode::Base, None, None) => {
+                let (client, blinded_elements) = Client::<_, S, Base>::blind([input], &mut rng)?;
+                (Box::new(client), blinded_elements)
+            }
+            (AnyMode::Verifiable, None, Some(verifying_key)) => {
+                let (client, blinded_elements) =
+                    Client::<_, S, Verifiable>::blind([input], verifying_key, &mut rng)?;
+                (Box::new(client), blinded_elements)
+            }
+            (AnyMode::Partial, Some(info), Some(verifying_key)) => {
+                let (client, blinded_elements) =
+                    Client::<_, S, Partial>::blind([input], info, verifying_key, &mut rng)?;
+                (Box::new(client), blinded_elements)
+            }
+            _ => return Err(DynError::InvalidParameters),
+        };
+
+    Ok((client, blinded_element.0.to_bytes().as_ref().to_vec()))
+}
+
+/// Decode a serialized blinded element.
+fn decode_blinded<S: Suite>(bytes: &[u8]) -> Result<[Blinded<S::Group>; 1], InvalidEncoding> {
+    Ok([Blinded(decode_element(bytes)?)])
+}
+
+/// Serialize an evaluated element, followed by the proof if there is one.
+fn encode_evaluation<S: Suite>(
+    evaluated_element: Evaluated<S::Group>,
+    proof: Option<Proof<<S::Group as Group>::Scalar>>,
+) -> Vec<u8> {
+    let mut evaluation = evaluated_element.0.to_bytes().as_ref().to_vec();
+    if let Some(proof) = proof {
+        evaluation.extend_from_slice(proof.c.to_repr().as_ref());
+        evaluation.extend_from_slice(proof.s.to_repr().as_ref());
+    }
+    evaluation
+}
+
+/// Decode an evaluation serialized by [`encode_evaluation`] with a proof.
+#[allow(clippy::type_complexity)]
+fn decode_evaluation<S: Suite>(
+    bytes: &[u8],
+) -> Result<([Evaluated<S::Group>; 1], Proof<<S::Group as Group>::Scalar>), InvalidEncoding> {
+    let element_len = <S::Group as GroupEncoding>::Repr::default().as_ref().len();
+    let scalar_len = <<S::Group as Group>::Scalar as PrimeField>::Repr::default()
+        .as_ref()
+        .len();
+    if bytes.len() != element_len + 2 * scalar_len {
+        return Err(InvalidEncoding);
+    }
+    let (element, proof) = bytes.split_at(element_len);
+    let (c, s) = proof.split_at(scalar_len);
+    Ok((
+        [Evaluated(decode_element(element)?)],
+        Proof {
+            c: decode_scalar(c)?,
+            s: decode_scalar(s)?,
+        },
+    ))
+}
+
+impl<S: Suite> ErasedServer for Server<S, Base> {
+    fn verifying_key(&self) -> Vec<u8> {
+        Server::<S, Base>::verifying_key(self)
+            .0
+            .to_bytes()
+            .as_ref()
+            .to_vec()
+    }
+
+    fn evaluate(
+        &self,
+        blinded_element: &[u8],
+        info: Option<Input<'_>>,
+        _: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, DynError> {
+        if info.is_some() {
+            return Err(DynError::InvalidParameters);
+        }
+        let [evaluated_element] =
+            Server::<S, Base>::evaluate(self, decode_blinded::<S>(blinded_element)?);
+        Ok(encode_evaluation::<S>(evaluated_element, None))
+    }
+}
+
+impl<S: Suite> ErasedServer for Server<S, Verifiable> {
+    fn verifying_key(&self) -> Vec<u8> {
+        Server::<S, Verifiable>::verifying_key(self)
+            .0
+            .to_bytes()
+            .as_ref()
+            .to_vec()
+    }
+
+    fn evaluate(
+        &self,
+        blinded_element: &[u8],
+        info: Option<Input<'_>>,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, DynError> {
+        if info.is_some() {
+            return Err(DynError::InvalidParameters);
+        }
+        let ([evaluated_element], proof) = Server::<S, Verifiable>::evaluate(
+            self,
+            decode_blinded::<S>(blinded_element)?,
+            &mut rng,
+        );
+        Ok(encode_evaluation::<S>(evaluated_element, Some(proof)))
+    }
+}
+
+impl<S: Suite> ErasedServer for Server<S, Partial> {
+    fn verifying_key(&self) -> Vec<u8> {
+        Server::<S, Partial>::verifying_key(self)
+            .0
+            .to_bytes()
+            .as_ref()
+            .to_vec()
+    }
+
+    fn evaluate(
+        &self,
+        blinded_element: &[u8],
+        info: Option<Input<'_>>,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, DynError> {
+        let info = info.ok_or(DynError::InvalidParameters)?;
+        let ([evaluated_element], proof) = Server::<S, Partial>::evaluate(
+            self,
+            decode_blinded::<S>(blinded_element)?,
+            info,
+            &mut rng,
+        )?;
+        Ok(encode_evaluation::<S>(evaluated_element, Some(proof)))
+    }
+}
+
+impl<S: Suite> ErasedClient for Client<'_, '_, 1, S, Base> {
+    fn finalize(self: Box<Self>, evaluation: &[u8]) -> Result<Vec<u8>, DynError> {
+        let evaluated_element: S::Group = decode_element(evaluation)?;
+        let [output] = Client::<1, S, Base>::finalize(*self, [Evaluated(evaluated_element)]);
+        Ok(output.to_vec())
+    }
+}
+
+impl<S: Suite> ErasedClient for Client<'_, '_, 1, S, Verifiable> {
+    fn finalize(self: Box<Self>, evaluation: &[u8]) -> Result<Vec<u8>, DynError> {
+        let (evaluated_elements, proof) = decode_evaluation::<S>(evaluation)?;
+        let [output] = Client::<1, S, Verifiable>::finalize(*self, evaluated_elements, proof)?;
+        Ok(output.to_vec())
+    }
+}
+
+impl<S: Suite> ErasedClient for Client<'_, '_, 1, S, Partial> {
+    fn finalize(self: Box<Self>, evaluation: &[u8]) -> Result<Vec<u8>, DynError> {
+        let (evaluated_elements, proof) = decode_evaluation::<S>(evaluation)?;
+        let [output] = Client::<1, S, Partial>::finalize(*self, evaluated_elements, proof)?;
+        Ok(output.to_vec())
+    }
+}
+
+/// Error returned by the dynamic layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DynError {
+    /// No implementation of the suite is registered.
+    UnknownSuite,
+    /// The `info` or verifying key was provided in a mode that does not use it, or is missing in
+    /// a mode that uses it.
+    InvalidParameters,
+    /// A serialized key, element or proof is invalid.
+    InvalidEncoding(InvalidEncoding),
+    /// The input hashes to the identity element.
+    InvalidInput(InvalidInput),
+    /// The proof of the server is invalid.
+    InvalidProof(InvalidProof),
+    /// The server's secret key combined with the `info` results in a zero scalar.
+    UndefinedInverse(UndefinedInverse),
+}
+
+impl From<InvalidEncoding> for DynError {
+    fn from(error: InvalidEncoding) -> Self {
+        DynError::InvalidEncoding(error)
+    }
+}
+
+impl From<InvalidInput> for DynError {
+    fn from(error: InvalidInput) -> Self {
+        DynError::InvalidInput(error)
+    }
+}
+
+impl From<InvalidProof> for DynError {
+    fn from(error: InvalidProof) -> Self {
+        DynError::InvalidProof(error)
+    }
+}
+
+impl From<UndefinedInverse> for DynError {
+    fn from(error: UndefinedInverse) -> Self {
+        DynError::UndefinedInverse(error)
+    }
+}
+
+impl core::fmt::Display for DynError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DynError::UnknownSuite => write!(f, "no implementation of the suite is registered"),
+            DynError::InvalidParameters => {
+                write!(f, "the parameters provided do not match the mode")
+            }
+            DynError::InvalidEncoding(e) => e.fmt(f),
+            DynError::InvalidInput(e) => e.fmt(f),
+            DynError::InvalidProof(e) => e.fmt(f),
+            DynError::UndefinedInverse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for DynError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            DynError::UnknownSuite | DynError::InvalidParameters => None,
+            DynError::InvalidEncoding(e) => Some(e),
+            DynError::InvalidInput(e) => Some(e),
+            DynError::InvalidProof(e) => Some(e),
+            DynError::UndefinedInverse(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Helpers for the binary encodings of this crate.
+//!
+//! Variable length fields are prefixed with their length as a big-endian `u16`, like in the
+//! transcripts of RFC 9497.
+
+use alloc::vec::Vec;
+
+use group::{Group, GroupEncoding, ff::PrimeField};
+
+use crate::InvalidEncoding;
+
+/// Append a length prefixed field.
+///
+/// # Panics
+///
+/// Panics if the field is longer than `u16::MAX` bytes.
+pub(crate) fn put(out: &mut Vec<u8>, field: &[u8]) {
+    let len = u16::try_from(field.len()).expect("field is shorter than 2^16 bytes");
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(field);
+}
+
+/// Reader over an encoding produced with [`put`].
+pub(crate) struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Reader(bytes)
+    }
+
+    /// Read `len` raw bytes.
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], InvalidEncoding> {
+        if self.0.len() < len {
+            return Err(InvalidEncoding);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, InvalidEncoding> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, InvalidEncoding> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    #[cfg_attr(not(feature = "keyfile"), allow(dead_code))]
+    pub(crate) fn u32(&mut self) -> Result<u32, InvalidEncoding> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a length prefixed field.
+    pub(crate) fn field(&mut self) -> Result<&'a [u8], InvalidEncoding> {
+        let len = self.u16()?;
+        self.bytes(len as usize)
+    }
+
+    /// Read a length prefixed group element, rejecting the identity.
+    pub(crate) fn element<E: Group + GroupEncoding>(&mut self) -> Result<E, InvalidEncoding> {
+        decode_element(self.field()?)
+    }
+
+    /// Read a length prefixed scalar.
+    pub(crate) fn scalar<F: PrimeField>(&mut self) -> Result<F, InvalidEncoding> {
+        decode_scalar(self.field()?)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, InvalidEncoding> {
+        let bytes = self.bytes(8)?;
+        Ok(u64::from_be_bytes(
+            bytes.try_into().expect("8 bytes were read"),
+        ))
+    }
+
+    /// The number of bytes left to read.
+    pub(crate) fn remaining(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Ensure every byte was read.
+    pub(crate) fn finish(self) -> Result<(), InvalidEncoding> {
+        if !self.0.is_empty() {
+            return Err(InvalidEncoding);
+        }
+        Ok(())
+    }
+}
+
+/// Decode a group element from its canonical encoding, rejecting the identity as
+/// `DeserializeElement` of RFC 9497.
+pub(crate) fn decode_element<E: Group + GroupEncoding>(bytes: &[u8]) -> Result<E, InvalidEncoding> {
+    let mut repr = E::Repr::default();
+    if repr.as_ref().len() != bytes.len() {
+        return Err(InvalidEncoding);
+    }
+    repr.as_mut().copy_from_slice(bytes);
+    E::from_bytes(&repr)
+        .into_option()
+        .filter(|element| !bool::from(element.is_identity()))
+        .ok_or(InvalidEncoding)
+}
+
+/// Decode a scalar from its canonical encoding.
+pub(crate) fn decode_scalar<F: PrimeField>(bytes: &[u8]) -> Result<F, InvalidEncoding> {
+    let mut repr = F::Repr::default();
+    if repr.as_ref().len() != bytes.len() {
+        return Err(InvalidEncoding);
+    }
+    repr.as_mut().copy_from_slice(bytes);
+    F::from_repr(repr).into_option().ok_or(InvalidEncoding)
+}
@@ -0,0 +1,245 @@
+//! Password hardening service with key rotation, in the style of [Pythia].
+//!
+//! A relying party (e.g. a web server) stores [`PasswordRecord`]s computed with the help of a
+//! [`HardeningServer`]. The tweak of each query identifies the user or the tenant, and selects the
+//! key it is evaluated with: the server derives an independent key for every tweak from its master
+//! seed, with `DeriveKeyPair` of RFC 9497 in the [`Verifiable`] mode. A stolen database of records
+//! is useless without querying the hardening server, which can rate limit queries per tweak, and
+//! which never learns the passwords.
+//!
+//! The hardening server can rotate its master seed at any time. It then issues an [`UpdateToken`]
+//! for every tweak, the ratio `k' / k` between the new and the previous keys of the tweak, with
+//! which relying parties update their records without knowing the passwords. The keys of different
+//! tweaks are unrelated, so tokens reveal nothing about the keys of other tweaks, and no number of
+//! them reveals any key. Update tokens are verified against the previous and new verifying keys of
+//! the tweak before being applied.
+//!
+//! The keys of a tweak are not tweaks of a shared key as in the [`Partial`](crate::mode::Partial)
+//! mode: the ratio between two tweaked keys `(k + m) / (k' + m)` for two tweaks `m` would reveal
+//! `k` and `k'`.
+//!
+//! [Pythia]: https://eprint.iacr.org/2015/644
+
+use core::marker::PhantomData;
+
+use group::{Group, GroupEncoding, ff::Field};
+use rand_core::RngCore;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    mode::Verifiable,
+    server::{InvalidSeed, Server},
+};
+
+/// The hardening server.
+///
+/// The master seed is zeroized on drop.
+pub struct HardeningServer<S: Suite> {
+    seed: [u8; 32],
+    suite: PhantomData<S>,
+}
+
+impl<S: Suite> Clone for HardeningServer<S> {
+    fn clone(&self) -> Self {
+        HardeningServer::new(self.seed)
+    }
+}
+
+impl<S: Suite> core::fmt::Debug for HardeningServer<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HardeningServer").finish_non_exhaustive()
+    }
+}
+
+impl<S: Suite> Drop for HardeningServer<S> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.seed);
+    }
+}
+
+impl<S: Suite> HardeningServer<S> {
+    /// Create a hardening server deriving the keys of the tweaks from the master `seed`.
+    pub fn new(seed: [u8; 32]) -> Self {
+        HardeningServer {
+            seed,
+            suite: PhantomData,
+        }
+    }
+
+    /// The verifying key of the `tweak`.
+    ///
+    /// This is the key to use when starting a [`Query`] to enroll a password with the `tweak`.
+    pub fn verifying_key(&self, tweak: Input<'_>) -> Result<VerifyingKey<S::Group>, InvalidSeed> {
+        Ok(Server::<S, Verifiable>::new(self.seed, tweak)?.verifying_key())
+    }
+
+    /// Evaluate the blinded password of a [`Query`], with the key of the `tweak`.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate(
+        &self,
+        blinded_element: Blinded<S::Group>,
+        tweak: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Evaluated<S::Group>, Proof<<S::Group as Group>::Scalar>), InvalidSeed> {
+        let server = Server::<S, Verifiable>::new(self.seed, tweak)?;
+        let ([evaluated_element], proof) = server.evaluate([blinded_element], rng);
+        Ok((evaluated_element, proof))
+    }
+
+    /// Rotate the master seed of the hardening server.
+    ///
+    /// The returned [`Rotation`] issues the [`UpdateToken`]s relying parties need to update their
+    /// records. It holds both the previous and the new seeds, and should be dropped as soon as
+    /// every token is issued.
+    pub fn rotate(&mut self, seed: [u8; 32]) -> Rotation<S> {
+        let previous = core::mem::replace(&mut self.seed, seed);
+        Rotation {
+            previous: HardeningServer::new(previous),
+            current: self.clone(),
+        }
+    }
+}
+
+/// A key rotation of a [`HardeningServer`].
+///
+/// Both seeds are zeroized on drop.
+pub struct Rotation<S: Suite> {
+    previous: HardeningServer<S>,
+    current: HardeningServer<S>,
+}
+
+impl<S: Suite> Rotation<S> {
+    /// Issue the update token for the records of the provided tweak.
+    pub fn update_token(&self, tweak: Input<'_>) -> Result<UpdateToken<S::Group>, InvalidSeed> {
+        let previous = Server::<S, Verifiable>::new(self.previous.seed, tweak)?;
+        let current = Server::<S, Verifiable>::new(self.current.seed, tweak)?;
+        let previous_inv = previous
+            .secret_key()
+            .expose_secret()
+            .invert()
+            .expect("derived keys are not zero");
+
+        Ok(UpdateToken {
+            delta: *current.secret_key().expose_secret() * previous_inv,
+            verifying_key: current.verifying_key(),
+        })
+    }
+}
+
+/// Token to update the [`PasswordRecord`]s of a tweak after a key rotation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpdateToken<E: Group> {
+    /// Ratio between the new and the previous keys of the tweak.
+    pub delta: E::Scalar,
+    /// The new verifying key of the hardening server.
+    pub verifying_key: VerifyingKey<E>,
+}
+
+/// The update token does not match the record it is applied to.
+///
+/// This is returned when the token was issued for another tweak, or by a server that does not hold
+/// the seed of the record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidToken;
+
+impl core::fmt::Display for InvalidToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the update token does not match the record")
+    }
+}
+
+impl core::error::Error for InvalidToken {}
+
+/// Hardened password stored by the relying party.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PasswordRecord<E> {
+    /// The unblinded element of the password, before it is hashed into an output.
+    pub element: E,
+    /// The verifying key of the tweak the record is bound to.
+    pub verifying_key: VerifyingKey<E>,
+}
+
+impl<E: Group> PasswordRecord<E> {
+    /// The verifying key of the tweak this record is bound to.
+    ///
+    /// This is the key to use when starting a [`Query`] to verify a password against this record.
+    pub fn verifying_key(&self) -> VerifyingKey<E> {
+        self.verifying_key
+    }
+
+    /// Update the record after a key rotation of the hardening server.
+    ///
+    /// The `token` must be the one of the tweak the record was enrolled with.
+    pub fn update(&mut self, token: &UpdateToken<E>) -> Result<(), InvalidToken> {
+        if bool::from(token.delta.is_zero())
+            || self.verifying_key.0 * token.delta != token.verifying_key.0
+        {
+            return Err(InvalidToken);
+        }
+
+        self.element *= token.delta;
+        self.verifying_key = token.verifying_key;
+        Ok(())
+    }
+}
+
+/// Query of the relying party to the hardening server, to enroll or verify a password.
+pub struct Query<'a, S: Suite> {
+    client: Client<'a, 'a, 1, S, Verifiable>,
+    verifying_key: VerifyingKey<S::Group>,
+}
+
+impl<'a, S: Suite> Query<'a, S> {
+    /// Blind the password.
+    ///
+    /// The blinded element and the tweak are sent to the hardening server, and the evaluation is
+    /// verified against the `verifying_key` of the tweak.
+    pub fn new(
+        password: Input<'a>,
+        verifying_key: VerifyingKey<S::Group>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, Blinded<S::Group>), InvalidInput> {
+        let (client, [blinded_element]) =
+            Client::<_, S, Verifiable>::blind([password], verifying_key, rng)?;
+        Ok((
+            Query {
+                client,
+                verifying_key,
+            },
+            blinded_element,
+        ))
+    }
+
+    /// Finish enrolling the password, producing the record to store.
+    pub fn enroll(
+        self,
+        evaluated_element: Evaluated<S::Group>,
+        proof: Proof<<S::Group as Group>::Scalar>,
+    ) -> Result<PasswordRecord<S::Group>, InvalidProof> {
+        let [element] = self.client.unblind([evaluated_element], proof)?;
+        Ok(PasswordRecord {
+            element,
+            verifying_key: self.verifying_key,
+        })
+    }
+
+    /// Finish verifying the password, returning whether it matches the record.
+    ///
+    /// The elements are compared in constant time.
+    pub fn verify(
+        self,
+        evaluated_element: Evaluated<S::Group>,
+        proof: Proof<<S::Group as Group>::Scalar>,
+        record: &PasswordRecord<S::Group>,
+    ) -> Result<bool, InvalidProof> {
+        let candidate = self.enroll(evaluated_element, proof)?;
+        let element = candidate
+            .element
+            .to_bytes()
+            .as_ref()
+            .ct_eq(record.element.to_bytes().as_ref());
+        Ok(bool::from(element) && candidate.verifying_key == record.verifying_key)
+    }
+}
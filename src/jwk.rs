@@ -0,0 +1,131 @@
+//! Import and export of keys as JSON Web Keys.
+//!
+//! There is no registered JWK key type for OPRF keys, so keys of every suite use the key type
+//! `"OPRF"`, with the [`Suite::IDENTIFIER`] of the suite in the `"suite"` parameter. The
+//! verifying key is in the `"x"` parameter, and the secret key in the `"d"` parameter, both
+//! serialized as in RFC 9497 and encoded in base64url without padding:
+//!
+//! ```json
+//! {"kty":"OPRF","suite":"P256-SHA256","x":"...","d":"..."}
+//! ```
+//!
+//! A verifying key can be decoded from the JWK of a secret key. When decoding a secret key, the
+//! verifying key must match it. Decoded secret keys are used with
+//! [`Server::from_secret_key`](crate::server::Server::from_secret_key).
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use group::{
+    Group, GroupEncoding,
+    ff::{Field, PrimeField},
+};
+use serde_json::{Map, Value};
+use zeroize::Zeroizing;
+
+use crate::{
+    InvalidEncoding, Suite, UnsupportedSuite, VerifyingKey,
+    encoding::{decode_element, decode_scalar},
+    server::SecretKey,
+};
+
+const KEY_TYPE: &str = "OPRF";
+
+/// The identifier of the suite, which must be valid UTF-8 to be encoded in JSON.
+fn suite<S: Suite>() -> Result<&'static str, UnsupportedSuite> {
+    core::str::from_utf8(S::IDENTIFIER).map_err(|_| UnsupportedSuite)
+}
+
+/// Encode the secret `key` as a JWK, with its verifying key.
+pub fn to_jwk<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+) -> Result<Zeroizing<String>, UnsupportedSuite> {
+    let suite = suite::<S>()?;
+    let verifying_key = S::Group::mul_by_generator(key.expose_secret()).to_bytes();
+    let mut secret_key = key.expose_secret().to_repr();
+
+    let d = Zeroizing::new(Base64UrlUnpadded::encode_string(secret_key.as_ref()));
+    zeroize::Zeroize::zeroize(secret_key.as_mut());
+    let mut jwk = Zeroizing::new(String::with_capacity(64 + suite.len() + d.len() * 3));
+    write!(
+        jwk,
+        r#"{{"kty":"{KEY_TYPE}","suite":{},"x":"{}","d":"{}"}}"#,
+        Value::from(suite),
+        Base64UrlUnpadded::encode_string(verifying_key.as_ref()),
+        d.as_str(),
+    )
+    .expect("writing to a string succeeds");
+    Ok(jwk)
+}
+
+/// Encode the `verifying_key` as a JWK.
+pub fn verifying_key_to_jwk<S: Suite>(
+    verifying_key: VerifyingKey<S::Group>,
+) -> Result<String, UnsupportedSuite> {
+    let mut jwk = Map::new();
+    jwk.insert("kty".into(), KEY_TYPE.into());
+    jwk.insert("suite".into(), suite::<S>()?.into());
+    jwk.insert(
+        "x".into(),
+        Base64UrlUnpadded::encode_string(verifying_key.0.to_bytes().as_ref()).into(),
+    );
+    Ok(serde_json::to_string(&Value::Object(jwk)).expect("the key is JSON encodable"))
+}
+
+/// Decode a secret key from a JWK.
+pub fn from_jwk<S: Suite>(
+    jwk: &str,
+) -> Result<SecretKey<<S::Group as Group>::Scalar>, InvalidEncoding> {
+    let mut jwk = parse::<S>(jwk)?;
+    let verifying_key = verifying_key::<S>(&jwk)?;
+    let Some(Value::String(d)) = jwk.get_mut("d") else {
+        return Err(InvalidEncoding);
+    };
+
+    let d = Zeroizing::new(core::mem::take(d));
+    let bytes = Zeroizing::new(Base64UrlUnpadded::decode_vec(&d).map_err(|_| InvalidEncoding)?);
+    let key = SecretKey::new(decode_scalar::<<S::Group as Group>::Scalar>(&bytes)?);
+    if bool::from(key.expose_secret().is_zero())
+        || S::Group::mul_by_generator(key.expose_secret()) != verifying_key.0
+    {
+        return Err(InvalidEncoding);
+    }
+
+    Ok(key)
+}
+
+/// Decode a verifying key from a JWK.
+pub fn verifying_key_from_jwk<S: Suite>(
+    jwk: &str,
+) -> Result<VerifyingKey<S::Group>, InvalidEncoding> {
+    let mut jwk = parse::<S>(jwk)?;
+    if let Some(Value::String(d)) = jwk.get_mut("d") {
+        zeroize::Zeroize::zeroize(d);
+    }
+    verifying_key::<S>(&jwk)
+}
+
+/// Parse the JWK, and check its key type and suite.
+fn parse<S: Suite>(jwk: &str) -> Result<Map<String, Value>, InvalidEncoding> {
+    let Ok(Value::Object(jwk)) = serde_json::from_str(jwk) else {
+        return Err(InvalidEncoding);
+    };
+    if jwk.get("kty").and_then(Value::as_str) != Some(KEY_TYPE)
+        || jwk.get("suite").and_then(Value::as_str).map(str::as_bytes) != Some(S::IDENTIFIER)
+    {
+        return Err(InvalidEncoding);
+    }
+    Ok(jwk)
+}
+
+fn verifying_key<S: Suite>(
+    jwk: &Map<String, Value>,
+) -> Result<VerifyingKey<S::Group>, InvalidEncoding> {
+    let x = jwk
+        .get("x")
+        .and_then(Value::as_str)
+        .ok_or(InvalidEncoding)?;
+    let bytes = Base64UrlUnpadded::decode_vec(x).map_err(|_| InvalidEncoding)?;
+    Ok(VerifyingKey(decode_element(&bytes)?))
+}
@@ -0,0 +1,247 @@
+//! Passphrase-encrypted key files.
+//!
+//! A key file stores the secret key of a [`Server`] at rest, encrypted with ChaCha20-Poly1305 under
+//! a key derived from a passphrase with Argon2id. The file starts with a header in the clear,
+//! authenticated as the associated data of the encryption:
+//!
+//! ```text
+//! magic       "OPRF-KEY"
+//! version     u8, currently 1
+//! mode        u8, the identifier of the mode of the server
+//! suite       u16 length prefixed identifier of the suite
+//! memory      u32, the Argon2id memory cost in KiB
+//! iterations  u32, the Argon2id time cost
+//! parallelism u32, the Argon2id degree of parallelism
+//! salt        16 bytes
+//! nonce       12 bytes
+//! ```
+//!
+//! It is followed by the encrypted serialized scalar, and the 16 bytes authentication tag. Integers
+//! are big-endian.
+//!
+//! A key file is only decrypted into a server of the suite and mode it was written for. The
+//! Argon2id parameters are read from the file, and files with parameters above the limits given to
+//! [`decrypt`] are rejected before deriving the key, so that a crafted file can't use an arbitrary
+//! amount of memory and time.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit, Nonce};
+use group::{
+    Group,
+    ff::{Field, PrimeField},
+};
+use rand_core::RngCore;
+use zeroize::Zeroizing;
+
+use crate::{
+    InvalidEncoding, Suite,
+    encoding::{Reader, decode_scalar, put},
+    mode::Mode,
+    server::{SecretKey, Server},
+};
+
+const MAGIC: &[u8; 8] = b"OPRF-KEY";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// The cost parameters of the Argon2id key derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KdfParams {
+    /// The memory cost, in KiB.
+    pub memory: u32,
+    /// The number of iterations.
+    pub iterations: u32,
+    /// The degree of parallelism.
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The minimum parameters recommended by OWASP: 19 MiB of memory, 2 iterations and 1 degree of
+    /// parallelism.
+    fn default() -> Self {
+        KdfParams {
+            memory: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Encrypt the secret key of the `server` with the `passphrase`.
+pub fn encrypt<S: Suite, M: Mode>(
+    server: &Server<S, M>,
+    passphrase: &[u8],
+    params: KdfParams,
+    rng: &mut impl RngCore,
+) -> Result<Vec<u8>, InvalidKdfParams> {
+    let mut salt = [0; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let mut file = Vec::new();
+    file.extend_from_slice(MAGIC);
+    file.push(VERSION);
+    file.push(M::IDENTIFIER.get());
+    put(&mut file, S::IDENTIFIER);
+    file.extend_from_slice(&params.memory.to_be_bytes());
+    file.extend_from_slice(&params.iterations.to_be_bytes());
+    file.extend_from_slice(&params.parallelism.to_be_bytes());
+    file.extend_from_slice(&salt);
+    file.extend_from_slice(&nonce);
+
+    let cipher = cipher(passphrase, params, &salt)?;
+    let mut secret_key = server.secret_key().expose_secret().to_repr();
+    let mut buffer = Zeroizing::new(Vec::with_capacity(secret_key.as_ref().len() + TAG_LEN));
+    buffer.extend_from_slice(secret_key.as_ref());
+    zeroize::Zeroize::zeroize(secret_key.as_mut());
+    cipher
+        .encrypt_in_place(Nonce::from_slice(&nonce), &file, &mut *buffer)
+        .expect("the key is shorter than the maximum plaintext length");
+
+    file.extend_from_slice(&buffer);
+    Ok(file)
+}
+
+/// Decrypt the server stored in the key `file` with the `passphrase`.
+///
+/// Fails with [`KeyFileError::ExcessiveKdfParams`] if any of the Argon2id parameters of the file is
+/// above the one of `max`.
+pub fn decrypt<S: Suite, M: Mode>(
+    file: &[u8],
+    passphrase: &[u8],
+    max: KdfParams,
+) -> Result<Server<S, M>, KeyFileError> {
+    let mut reader = Reader::new(file);
+    if reader.bytes(MAGIC.len())? != MAGIC {
+        return Err(KeyFileError::InvalidEncoding);
+    }
+    if reader.u8()? != VERSION {
+        return Err(KeyFileError::UnsupportedVersion);
+    }
+    if reader.u8()? != M::IDENTIFIER.get() {
+        return Err(KeyFileError::ModeMismatch);
+    }
+    if reader.field()? != S::IDENTIFIER {
+        return Err(KeyFileError::SuiteMismatch);
+    }
+    let params = KdfParams {
+        memory: reader.u32()?,
+        iterations: reader.u32()?,
+        parallelism: reader.u32()?,
+    };
+    if params.memory > max.memory
+        || params.iterations > max.iterations
+        || params.parallelism > max.parallelism
+    {
+        return Err(KeyFileError::ExcessiveKdfParams);
+    }
+    let salt = reader.bytes(SALT_LEN)?;
+    let nonce: [u8; NONCE_LEN] = reader
+        .bytes(NONCE_LEN)?
+        .try_into()
+        .expect("length was read");
+    let header = &file[..file.len() - reader.remaining()];
+
+    let ciphertext = reader.bytes(reader.remaining())?;
+    let key_len = <<S::Group as Group>::Scalar as PrimeField>::Repr::default()
+        .as_ref()
+        .len();
+    if ciphertext.len() != key_len + TAG_LEN {
+        return Err(KeyFileError::InvalidEncoding);
+    }
+
+    let cipher = cipher(passphrase, params, salt).map_err(|_| KeyFileError::InvalidEncoding)?;
+    let mut buffer = Zeroizing::new(ciphertext.to_vec());
+    cipher
+        .decrypt_in_place(Nonce::from_slice(&nonce), header, &mut *buffer)
+        .map_err(|_| KeyFileError::DecryptionFailed)?;
+
+    let key = SecretKey::new(decode_scalar::<<S::Group as Group>::Scalar>(&buffer)?);
+    if key.expose_secret().is_zero().into() {
+        return Err(KeyFileError::InvalidEncoding);
+    }
+    Ok(Server::from_secret_key(key))
+}
+
+/// Derive the cipher from the `passphrase` with Argon2id.
+fn cipher(
+    passphrase: &[u8],
+    params: KdfParams,
+    salt: &[u8],
+) -> Result<ChaCha20Poly1305, InvalidKdfParams> {
+    let params = Params::new(
+        params.memory,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|_| InvalidKdfParams)?;
+    let mut key = Zeroizing::new([0; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, salt, &mut *key)
+        .map_err(|_| InvalidKdfParams)?;
+    Ok(ChaCha20Poly1305::new_from_slice(&*key).expect("the key is 32 bytes"))
+}
+
+/// The Argon2id parameters are out of range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidKdfParams;
+
+impl fmt::Display for InvalidKdfParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the key derivation parameters are out of range")
+    }
+}
+
+impl core::error::Error for InvalidKdfParams {}
+
+/// Error returned when decrypting a key file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyFileError {
+    /// The file is not a valid key file.
+    InvalidEncoding,
+    /// The file was written with an unsupported version of the format.
+    UnsupportedVersion,
+    /// The file holds a key of another suite.
+    SuiteMismatch,
+    /// The file holds a key of another mode.
+    ModeMismatch,
+    /// The Argon2id parameters of the file are above the limits.
+    ExcessiveKdfParams,
+    /// The passphrase is wrong, or the file was tampered with.
+    DecryptionFailed,
+}
+
+impl From<InvalidEncoding> for KeyFileError {
+    fn from(_: InvalidEncoding) -> Self {
+        KeyFileError::InvalidEncoding
+    }
+}
+
+impl fmt::Display for KeyFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyFileError::InvalidEncoding => write!(f, "the key file is malformed"),
+            KeyFileError::UnsupportedVersion => {
+                write!(f, "the version of the key file is not supported")
+            }
+            KeyFileError::SuiteMismatch => write!(f, "the key file holds a key of another suite"),
+            KeyFileError::ModeMismatch => write!(f, "the key file holds a key of another mode"),
+            KeyFileError::ExcessiveKdfParams => {
+                write!(
+                    f,
+                    "the key derivation parameters of the key file are above the limits"
+                )
+            }
+            KeyFileError::DecryptionFailed => write!(f, "the key file could not be decrypted"),
+        }
+    }
+}
+
+impl core::error::Error for KeyFileError {}
@@ -0,0 +1,138 @@
+//! Per-tenant keys derived from a master seed.
+//!
+//! A service evaluating the OPRF for many tenants can give each of them its own key without
+//! storing them: the [`KeyRing`] derives the server of a tenant with [`Server::new`], i.e. the
+//! `DeriveKeyPair` method of RFC 9497, using the master seed and the identifier of the tenant as
+//! the `info`. The keys of a tenant are stable for a given seed, so they can be derived again on
+//! any replica of the service.
+//!
+//! Derived servers are cached, up to a fixed capacity. When the cache is full, the least recently
+//! used server is evicted, and its key is zeroized.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::num::NonZeroUsize;
+
+use crate::{
+    Input, Suite, VerifyingKey,
+    mode::Mode,
+    server::{InvalidSeed, Server},
+};
+
+/// Servers of many tenants, derived from a master seed.
+pub struct KeyRing<S: Suite, M: Mode> {
+    seed: [u8; 32],
+    capacity: NonZeroUsize,
+    servers: BTreeMap<Vec<u8>, (u64, Server<S, M>)>,
+    clock: u64,
+}
+
+impl<S: Suite, M: Mode> core::fmt::Debug for KeyRing<S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KeyRing")
+            .field("capacity", &self.capacity)
+            .field("len", &self.servers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Suite, M: Mode> zeroize::Zeroize for KeyRing<S, M> {
+    fn zeroize(&mut self) {
+        self.seed.zeroize();
+        self.clear();
+    }
+}
+
+impl<S: Suite, M: Mode> Drop for KeyRing<S, M> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl<S: Suite, M: Mode> zeroize::ZeroizeOnDrop for KeyRing<S, M> {}
+
+impl<S: Suite, M: Mode> KeyRing<S, M> {
+    /// Derive the servers of the tenants from the master `seed`, caching at most `capacity` of
+    /// them.
+    pub fn new(seed: [u8; 32], capacity: NonZeroUsize) -> Self {
+        KeyRing {
+            seed,
+            capacity,
+            servers: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// The server of the `tenant`, derived if it isn't cached.
+    pub fn server(&mut self, tenant: Input<'_>) -> Result<&Server<S, M>, InvalidSeed> {
+        self.clock += 1;
+        let clock = self.clock;
+
+        if !self.servers.contains_key(tenant.as_ref()) {
+            let server = Server::new(self.seed, tenant)?;
+            if self.servers.len() >= self.capacity.get() {
+                self.evict_least_recently_used();
+            }
+            self.servers
+                .insert(tenant.as_ref().to_vec(), (clock, server));
+        }
+
+        let (last_used, server) = self
+            .servers
+            .get_mut(tenant.as_ref())
+            .expect("the server was just inserted");
+        *last_used = clock;
+        Ok(server)
+    }
+
+    /// The verifying key of the `tenant`, to be published to its clients.
+    pub fn verifying_key(
+        &mut self,
+        tenant: Input<'_>,
+    ) -> Result<VerifyingKey<S::Group>, InvalidSeed> {
+        Ok(self.server(tenant)?.verifying_key())
+    }
+
+    /// Whether the server of the `tenant` is cached.
+    pub fn contains(&self, tenant: Input<'_>) -> bool {
+        self.servers.contains_key(tenant.as_ref())
+    }
+
+    /// The number of cached servers.
+    pub fn len(&self) -> usize {
+        self.servers.len()
+    }
+
+    /// Whether no server is cached.
+    pub fn is_empty(&self) -> bool {
+        self.servers.is_empty()
+    }
+
+    /// Evict the server of the `tenant` from the cache, and zeroize its key.
+    ///
+    /// The server is derived again the next time it is needed.
+    pub fn evict(&mut self, tenant: Input<'_>) {
+        if let Some((_, mut server)) = self.servers.remove(tenant.as_ref()) {
+            zeroize::Zeroize::zeroize(&mut server);
+        }
+    }
+
+    /// Evict every server from the cache, and zeroize their keys.
+    pub fn clear(&mut self) {
+        for (_, (_, mut server)) in core::mem::take(&mut self.servers) {
+            zeroize::Zeroize::zeroize(&mut server);
+        }
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let tenant = self
+            .servers
+            .iter()
+            .min_by_key(|(_, (last_used, _))| *last_used)
+            .map(|(tenant, _)| tenant.clone());
+        if let Some(tenant) = tenant
+            && let Some((_, mut server)) = self.servers.remove(&tenant)
+        {
+            zeroize::Zeroize::zeroize(&mut server);
+        }
+    }
+}
@@ -5,17 +5,69 @@
 //!
 //! Usage of this crate depends on whether you want to use an already existing ciphersuite as a
 //! [`client`] or a [`server`], or want to implement a new OPRF [`Suite`].
+//!
+//! # Features
+//!
+//! - `alloc` (default): modules that need dynamically sized collections.
+//! - `rayon`: process the elements of batches in parallel when hashing to the group, multiplying
+//!   by scalars, computing the composites of proofs and finalizing outputs. The outputs and proofs
+//!   are the same as without the feature.
+//! - `pkcs8`: import and export of keys in the PKCS#8, SEC1 and SubjectPublicKeyInfo formats, in
+//!   [`pkix`].
+//! - `json`: JSON encoding of [`directory::KeyDirectory`].
+//! - `jwk`: import and export of keys as JSON Web Keys, in [`jwk`]. Implies `json`.
+//! - `keyfile`: passphrase-encrypted key files, in [`keyfile`].
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod backend;
+#[cfg(feature = "alloc")]
+pub mod batch;
+#[cfg(feature = "alloc")]
+pub mod breach;
 pub mod client;
+pub mod convergent;
+#[cfg(feature = "alloc")]
+pub mod directory;
+#[cfg(feature = "alloc")]
+pub mod dynamic;
+#[cfg(feature = "alloc")]
+mod encoding;
+pub mod hardening;
+#[cfg(feature = "jwk")]
+pub mod jwk;
+#[cfg(feature = "keyfile")]
+pub mod keyfile;
+#[cfg(feature = "alloc")]
+pub mod keyring;
+pub mod masked;
 pub mod mode;
+mod msm;
+#[cfg(feature = "pkcs8")]
+pub mod pkix;
+pub mod possession;
+#[cfg(feature = "alloc")]
+pub mod ppss;
+#[cfg(feature = "alloc")]
+pub mod prepared;
+pub mod primitives;
+#[cfg(feature = "alloc")]
+pub mod pseudonym;
+#[cfg(feature = "alloc")]
+pub mod receipt;
+#[cfg(feature = "alloc")]
+pub mod rotation;
+pub mod search;
 pub mod server;
+pub mod vrf;
 
 use core::ops::Shl;
 
 use digest::{
-    Digest,
+    Digest, Output,
     array::{Array, ArraySize, AsArrayRef, AssocArraySize},
     consts::{B1, True, U65536},
     typenum::{Double, IsLess},
@@ -35,6 +87,16 @@ pub trait Suite {
     /// The identifier for this ciphersuite.
     const IDENTIFIER: &'static [u8];
 
+    /// An application-specific domain separation tag, empty by default.
+    ///
+    /// This is not defined in RFC 9497. When not empty, `"-App-" || I2OSP(len(DOMAIN), 2) ||
+    /// DOMAIN` is appended to the context string of every hash to the group, hash to a scalar and
+    /// key derivation, so that two
+    /// applications using the same suite and keys compute unrelated functions. Outputs, proofs and
+    /// derived keys are then not those of RFC 9497, and only interoperate with implementations
+    /// using the same tag.
+    const DOMAIN: &'static [u8] = b"";
+
     /// The prime-order group used in this ciphersuite.
     type Group: PrimeGroup<Scalar: PrimeField<Repr: AsArrayRef<u8, Size: IsLess<U65536, Output = True>>>>
         + GroupEncoding<Repr: AsArrayRef<u8, Size: IsLess<U65536, Output = True>>>;
@@ -49,6 +111,32 @@ pub trait Suite {
 
     /// Hash to scalar routine used by this ciphersuite.
     fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as Group>::Scalar;
+
+    /// Multi-scalar multiplication, computing the sum of `scalars[i] * elements[i]`.
+    ///
+    /// This is used to compute the composites of proofs and to verify them, which only involve
+    /// public values, so it does not need to run in constant time. `scalars` and `elements` always
+    /// have the same length.
+    ///
+    /// The default implementation is a generic Straus, or Pippenger for large inputs with the
+    /// `alloc` feature. Override it if the group provides a faster implementation.
+    fn multiscalar_mul(
+        scalars: &[<Self::Group as Group>::Scalar],
+        elements: &[Self::Group],
+    ) -> Self::Group {
+        msm::multiscalar_mul(scalars, elements)
+    }
+
+    /// Serialize `elements` into `out`, which always has the same length.
+    ///
+    /// The default implementation serializes each element with [`GroupEncoding::to_bytes`].
+    /// Override it if the group uses projective coordinates, to normalize all the elements with a
+    /// single inversion.
+    fn batch_to_bytes(elements: &[Self::Group], out: &mut [<Self::Group as GroupEncoding>::Repr]) {
+        for (element, out) in elements.iter().zip(out) {
+            *out = element.to_bytes();
+        }
+    }
 }
 
 /// A blinded element.
@@ -87,6 +175,30 @@ pub struct Proof<S> {
     /// Response scalar.
     pub s: S,
 }
+
+/// Commitments of a proof of evaluation.
+///
+/// The [`Proof`]s of RFC 9497 are in the compact form: the verifier recomputes the composites and
+/// the commitments of the server, and hashes them to check the challenge, so each proof is
+/// verified on its own. A server that also sends its commitments lets the verifier check the
+/// challenge with a hash alone and fold the remaining equations of many proofs into a single
+/// multi-scalar multiplication, see [`batch::verify`].
+///
+/// This is not defined in RFC 9497.
+///
+/// [`batch::verify`]: crate::batch::verify
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Commitments<E> {
+    /// The composite of the blinded elements, `M`.
+    pub m: E,
+    /// The composite of the evaluated elements, `Z`.
+    pub z: E,
+    /// The commitment `t2 = r * A`.
+    pub t2: E,
+    /// The commitment `t3 = r * M`.
+    pub t3: E,
+}
+
 impl<S> Proof<S>
 where
     S: PrimeField<Repr: AsArrayRef<u8, Size: Shl<B1, Output: ArraySize>>>,
@@ -135,6 +247,35 @@ impl core::fmt::Display for TooLong {
 
 impl core::error::Error for TooLong {}
 
+/// The encoding is malformed.
+///
+/// This is returned when decoding bytes that were not produced by the matching encoder, or that
+/// were produced for another suite or mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidEncoding;
+
+impl core::fmt::Display for InvalidEncoding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the encoding is malformed")
+    }
+}
+
+impl core::error::Error for InvalidEncoding {}
+
+/// The suite is not supported by the encoding.
+///
+/// This is returned when encoding keys of a suite in a format that has no identifier for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnsupportedSuite;
+
+impl core::fmt::Display for UnsupportedSuite {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the suite is not supported by the encoding")
+    }
+}
+
+impl core::error::Error for UnsupportedSuite {}
+
 impl<'a> TryFrom<&'a [u8]> for Input<'a> {
     type Error = TooLong;
 
@@ -161,57 +302,194 @@ impl<'a> From<Input<'a>> for &'a [u8] {
 /// Implementation of [`GenerateProof`] from RFC 9497.
 ///
 /// [`GenerateProof`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-2.2.1-3
-fn generate_proof<const N: usize, S: Suite, M: Mode>(
+fn generate_proof<S: Suite, M: Mode>(
     k: <S::Group as Group>::Scalar,
     a: S::Group,
     b: S::Group,
-    c: [S::Group; N],
-    d: [S::Group; N],
+    c: &[S::Group],
+    d: &[S::Group],
     rng: &mut impl RngCore,
 ) -> Proof<<S::Group as Group>::Scalar> {
-    let (m, z) = compute_composites_fast::<N, S, M>(k, b, c, d);
+    generate_proof_with_seed::<S, M>(k, a, b, &composite_seed::<S, M>(b), c, d, |_, _| {
+        <S::Group as Group>::Scalar::random(rng)
+    })
+    .0
+}
 
-    let r = <S::Group as Group>::Scalar::random(rng);
+/// [`generate_proof`] with the seed of the composites already computed by [`composite_seed`], and
+/// the nonce `r` computed by `nonce(m, z)` from the composites.
+///
+/// Also returns the [`Commitments`] of the proof.
+fn generate_proof_with_seed<S: Suite, M: Mode>(
+    k: <S::Group as Group>::Scalar,
+    a: S::Group,
+    b: S::Group,
+    seed: &Output<S::Hash>,
+    c: &[S::Group],
+    d: &[S::Group],
+    nonce: impl FnOnce(S::Group, S::Group) -> <S::Group as Group>::Scalar,
+) -> (Proof<<S::Group as Group>::Scalar>, Commitments<S::Group>) {
+    let (m, z) = compute_composites_fast::<S, M>(k, seed, c, d);
+
+    let r = nonce(m, z);
     let t2 = a * r;
     let t3 = m * r;
 
-    let bm = b.to_bytes();
-    let a0 = m.to_bytes();
-    let a1 = z.to_bytes();
-    let a2 = t2.to_bytes();
-    let a3 = t3.to_bytes();
+    let c = challenge::<S, M>(b, m, z, t2, t3);
+    let s = r - c * k;
 
-    let challenge_transcript = [
-        &(bm.as_ref().len() as u16).to_be_bytes(),
-        bm.as_ref(),
+    (Proof { c, s }, Commitments { m, z, t2, t3 })
+}
+
+/// Derive the nonce of a proof from the secret key `k`, the composites `m` and `z`, and
+/// `randomness`.
+///
+/// This is not defined in RFC 9497. The nonce is computed as
+/// `HashToScalar(I2OSP(len(k), 2) || k || I2OSP(len(m), 2) || m || I2OSP(len(z), 2) || z ||
+/// I2OSP(len(randomness), 2) || randomness || "Nonce")`. A proof is entirely determined by `k`, `m`,
+/// `z` and its nonce, so with empty `randomness` the same evaluation always gets the same proof,
+/// and different evaluations never share a nonce, like the signatures of RFC 6979.
+fn derive_nonce<S: Suite, M: Mode>(
+    k: <S::Group as Group>::Scalar,
+    m: S::Group,
+    z: S::Group,
+    randomness: &[u8],
+) -> <S::Group as Group>::Scalar {
+    let mut km = k.to_repr();
+    let mut elements = [Default::default(); 2];
+    S::batch_to_bytes(&[m, z], &mut elements);
+    let [a0, a1] = elements;
+
+    let nonce_transcript = [
+        &(km.as_ref().len() as u16).to_be_bytes(),
+        km.as_ref(),
         &(a0.as_ref().len() as u16).to_be_bytes(),
         a0.as_ref(),
         &(a1.as_ref().len() as u16).to_be_bytes(),
         a1.as_ref(),
-        &(a2.as_ref().len() as u16).to_be_bytes(),
-        a2.as_ref(),
-        &(a3.as_ref().len() as u16).to_be_bytes(),
-        a3.as_ref(),
-        b"Challenge",
+        &(randomness.len() as u16).to_be_bytes(),
+        randomness,
+        b"Nonce",
     ];
-
-    let c = hash_to_scalar::<S, M>(&challenge_transcript);
-    let s = r - c * k;
-
-    Proof { c, s }
+    let r = hash_to_scalar::<S, M>(&nonce_transcript);
+    zeroize::Zeroize::zeroize(km.as_mut());
+    r
 }
 
 // Implementation of [`ComputeCompositesFast`] from RFC 9497.
 //
+// The seed is computed by [`composite_seed`].
+//
 // [`ComputeCompositesFast`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-2.2.1-5
-fn compute_composites_fast<const N: usize, S: Suite, M: Mode>(
+fn compute_composites_fast<S: Suite, M: Mode>(
     k: <S::Group as Group>::Scalar,
+    seed: &Output<S::Hash>,
+    c: &[S::Group],
+    d: &[S::Group],
+) -> (S::Group, S::Group) {
+    let m = compute_composite::<S, M>(seed, c, d);
+    let z = m * k;
+    (m, z)
+}
+
+/// The composite `m` of [`compute_composites_fast`], for a server whose key is held by a
+/// [`KeyBackend`](backend::KeyBackend), which computes `z` itself.
+fn compute_composite<S: Suite, M: Mode>(
+    seed: &Output<S::Hash>,
+    c: &[S::Group],
+    d: &[S::Group],
+) -> S::Group {
+    let mut m = S::Group::identity();
+    composite_scalars::<S, M>(seed, c, d, |di, c, _| {
+        m += S::multiscalar_mul(di, c);
+    });
+    m
+}
+
+/// Implementation of [`VerifyProof`] from RFC 9497.
+///
+/// [`VerifyProof`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-2.2.2-2
+fn verify_proof<S: Suite, M: Mode>(
+    a: S::Group,
+    b: S::Group,
+    c: &[S::Group],
+    d: &[S::Group],
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> bool {
+    verify_proof_with_seed::<S, M>(a, b, &composite_seed::<S, M>(b), c, d, proof)
+}
+
+/// [`verify_proof`] with the seed of the composites already computed by [`composite_seed`].
+fn verify_proof_with_seed<S: Suite, M: Mode>(
+    a: S::Group,
+    b: S::Group,
+    seed: &Output<S::Hash>,
+    c: &[S::Group],
+    d: &[S::Group],
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> bool {
+    verify_proof_with::<S, M>(
+        b,
+        seed,
+        |s, c| S::multiscalar_mul(&[s, c], &[a, b]),
+        c,
+        d,
+        proof,
+    )
+}
+
+/// [`verify_proof_with_seed`] where `t2 = a * s + b * c` is computed by `t2(s, c)`.
+///
+/// This lets callers with precomputed tables for `a` and `b` use them.
+fn verify_proof_with<S: Suite, M: Mode>(
     b: S::Group,
-    c: [S::Group; N],
-    d: [S::Group; N],
+    seed: &Output<S::Hash>,
+    t2: impl FnOnce(<S::Group as Group>::Scalar, <S::Group as Group>::Scalar) -> S::Group,
+    c: &[S::Group],
+    d: &[S::Group],
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> bool {
+    if c.len() != d.len() || c.len() > MAX_ELEMENTS {
+        return false;
+    }
+    let (m, z) = compute_composites::<S, M>(seed, c, d);
+    let c = proof.c;
+    let s = proof.s;
+
+    let t2 = t2(s, c);
+    let t3 = S::multiscalar_mul(&[s, c], &[m, z]);
+
+    let expected_c = challenge::<S, M>(b, m, z, t2, t3);
+
+    expected_c == c
+}
+
+/// Implementation of [`ComputeComposites`] from RFC 9497.
+///
+/// The seed is computed by [`composite_seed`].
+///
+/// [`ComputeComposites`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-2.2.2-4
+fn compute_composites<S: Suite, M: Mode>(
+    seed: &Output<S::Hash>,
+    c: &[S::Group],
+    d: &[S::Group],
 ) -> (S::Group, S::Group) {
+    let mut m = S::Group::identity();
+    let mut z = S::Group::identity();
+    composite_scalars::<S, M>(seed, c, d, |di, c, d| {
+        m += S::multiscalar_mul(di, c);
+        z += S::multiscalar_mul(di, d);
+    });
+
+    (m, z)
+}
+
+/// The seed of the composites, which only depends on `b`.
+///
+/// Shared by [`compute_composites`] and [`compute_composites_fast`].
+fn composite_seed<S: Suite, M: Mode>(b: S::Group) -> Output<S::Hash> {
     let bm = b.to_bytes();
-    let seed_dst: [&[u8]; 5] = context_string!(b"Seed-"; <S, M>);
+    let seed_dst: [&[u8]; 8] = context_string!(b"Seed-"; <S, M>);
     let mut hasher = S::Hash::new();
     let seed_transcript = [
         &(bm.as_ref().len() as u16).to_be_bytes(),
@@ -222,53 +500,105 @@ fn compute_composites_fast<const N: usize, S: Suite, M: Mode>(
         seed_dst[2],
         seed_dst[3],
         seed_dst[4],
+        seed_dst[5],
+        seed_dst[6],
     ];
     seed_transcript.iter().for_each(|s| hasher.update(s));
-    let seed = hasher.finalize();
+    hasher.finalize()
+}
 
-    let mut m = S::Group::identity();
-    for i in 0..N {
-        let ci = c[i].to_bytes();
-        let di = d[i].to_bytes();
-        let composite_transcript = [
-            &(seed.len() as u16).to_be_bytes(),
-            seed.as_slice(),
-            &(i as u16).to_be_bytes(),
-            &(ci.as_ref().len() as u16).to_be_bytes(),
-            ci.as_ref(),
-            &(di.as_ref().len() as u16).to_be_bytes(),
-            di.as_ref(),
-            b"Composite",
-        ];
-        let di = hash_to_scalar::<S, M>(&composite_transcript);
-        m = c[i] * di + m;
+/// The maximum number of elements covered by a proof, as their index is encoded as `I2OSP(i, 2)`
+/// in the composites.
+const MAX_ELEMENTS: usize = 1 << 16;
+
+/// Compute the scalars `di` of the composites, and call `f` with them and the matching elements of
+/// `c` and `d`.
+///
+/// With the `alloc` feature, `f` is called once for all the elements. Otherwise, it is called on
+/// chunks of a fixed size so that the scalars fit on the stack.
+///
+/// # Panics
+///
+/// Panics if `c` has more than [`MAX_ELEMENTS`] elements, which callers reject up front.
+fn composite_scalars<S: Suite, M: Mode>(
+    seed: &Output<S::Hash>,
+    c: &[S::Group],
+    d: &[S::Group],
+    mut f: impl FnMut(&[<S::Group as Group>::Scalar], &[S::Group], &[S::Group]),
+) {
+    assert!(
+        c.len() <= MAX_ELEMENTS,
+        "a proof covers at most 2^16 elements"
+    );
+    #[cfg(feature = "alloc")]
+    let chunk_size = c.len().max(1);
+    #[cfg(not(feature = "alloc"))]
+    let chunk_size = 8;
+
+    for (chunk, (c, d)) in c.chunks(chunk_size).zip(d.chunks(chunk_size)).enumerate() {
+        #[cfg(feature = "alloc")]
+        let (mut elements, mut di) = (
+            alloc::vec![Default::default(); 2 * c.len()],
+            alloc::vec![<S::Group as Group>::Scalar::ZERO; c.len()],
+        );
+        #[cfg(not(feature = "alloc"))]
+        let (mut elements, mut di) = (
+            [Default::default(); 16],
+            [<S::Group as Group>::Scalar::ZERO; 8],
+        );
+
+        let (ci, rest) = elements.split_at_mut(c.len());
+        let di_bytes = &mut rest[..d.len()];
+        S::batch_to_bytes(c, ci);
+        S::batch_to_bytes(d, di_bytes);
+
+        let composite_scalar = |i: usize, di: &mut <S::Group as Group>::Scalar| {
+            let (ci, di_bytes) = (&ci[i], &di_bytes[i]);
+            let composite_transcript = [
+                &(seed.len() as u16).to_be_bytes(),
+                seed.as_slice(),
+                &((chunk * chunk_size + i) as u16).to_be_bytes(),
+                &(ci.as_ref().len() as u16).to_be_bytes(),
+                ci.as_ref(),
+                &(di_bytes.as_ref().len() as u16).to_be_bytes(),
+                di_bytes.as_ref(),
+                b"Composite",
+            ];
+            *di = hash_to_scalar::<S, M>(&composite_transcript);
+        };
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::iter::{
+                IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+            };
+
+            di[..c.len()]
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(i, di)| composite_scalar(i, di));
+        }
+        #[cfg(not(feature = "rayon"))]
+        di[..c.len()]
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, di)| composite_scalar(i, di));
+        f(&di[..c.len()], c, d);
     }
-    let z = m * k;
-    (m, z)
 }
 
-/// Implementation of [`VerifyProof`] from RFC 9497.
+/// The challenge of a proof.
 ///
-/// [`VerifyProof`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-2.2.2-2
-fn verify_proof<const N: usize, S: Suite, M: Mode>(
-    a: S::Group,
+/// Shared by [`generate_proof`] and [`verify_proof`].
+fn challenge<S: Suite, M: Mode>(
     b: S::Group,
-    c: [S::Group; N],
-    d: [S::Group; N],
-    proof: Proof<<S::Group as Group>::Scalar>,
-) -> bool {
-    let (m, z) = compute_composites::<N, S, M>(b, c, d);
-    let c = proof.c;
-    let s = proof.s;
-
-    let t2 = a * s + b * c;
-    let t3 = m * s + z * c;
-
-    let bm = b.to_bytes();
-    let a0 = m.to_bytes();
-    let a1 = z.to_bytes();
-    let a2 = t2.to_bytes();
-    let a3 = t3.to_bytes();
+    m: S::Group,
+    z: S::Group,
+    t2: S::Group,
+    t3: S::Group,
+) -> <S::Group as Group>::Scalar {
+    let mut elements = [Default::default(); 5];
+    S::batch_to_bytes(&[b, m, z, t2, t3], &mut elements);
+    let [bm, a0, a1, a2, a3] = elements;
 
     let challenge_transcript = [
         &(bm.as_ref().len() as u16).to_be_bytes(),
@@ -284,60 +614,57 @@ fn verify_proof<const N: usize, S: Suite, M: Mode>(
         b"Challenge",
     ];
 
-    let expected_c = hash_to_scalar::<S, M>(&challenge_transcript);
-
-    expected_c == c
+    hash_to_scalar::<S, M>(&challenge_transcript)
 }
 
-/// Implementation of [`ComputeComposites`] from RFC 9497.
+/// The hashing step of [`Finalize`] in RFC 9497.
 ///
-/// [`ComputeComposites`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-2.2.2-4
-fn compute_composites<const N: usize, S: Suite, M: Mode>(
-    b: S::Group,
-    c: [S::Group; N],
-    d: [S::Group; N],
-) -> (S::Group, S::Group) {
-    let bm = b.to_bytes();
-    let seed_dst: [&[u8]; 5] = context_string!(b"Seed-"; <S, M>);
-    let mut hasher = S::Hash::new();
-    let seed_transcript = [
-        &(bm.as_ref().len() as u16).to_be_bytes(),
-        bm.as_ref(),
-        &(seed_dst.iter().map(|s| s.len()).sum::<usize>() as u16).to_be_bytes(),
-        seed_dst[0],
-        seed_dst[1],
-        seed_dst[2],
-        seed_dst[3],
-        seed_dst[4],
-    ];
-    seed_transcript.iter().for_each(|s| hasher.update(s));
-    let seed = hasher.finalize();
-
-    let mut m = S::Group::identity();
-    let mut z = S::Group::identity();
-    for i in 0..N {
-        let ci = c[i].to_bytes();
-        let di = d[i].to_bytes();
-        let composite_transcript = [
-            &(seed.len() as u16).to_be_bytes(),
-            seed.as_slice(),
-            &(i as u16).to_be_bytes(),
-            &(ci.as_ref().len() as u16).to_be_bytes(),
-            ci.as_ref(),
-            &(di.as_ref().len() as u16).to_be_bytes(),
-            di.as_ref(),
-            b"Composite",
-        ];
-        let di = hash_to_scalar::<S, M>(&composite_transcript);
-
-        m = c[i] * di + m;
-        z = d[i] * di + z;
+/// The `info` is only provided in the `mode::Partial` mode.
+///
+/// [`Finalize`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.1-7
+fn finalize_hash<S: Suite>(
+    input: Input<'_>,
+    info: Option<Input<'_>>,
+    unblinded_element: S::Group,
+) -> Output<S::Hash> {
+    let unblinded_element = unblinded_element.to_bytes();
+
+    let mut digest = S::Hash::new();
+    digest.update((input.as_ref().len() as u16).to_be_bytes());
+    digest.update(input.as_ref());
+    if let Some(info) = info {
+        digest.update((info.as_ref().len() as u16).to_be_bytes());
+        digest.update(info.as_ref());
     }
+    digest.update((unblinded_element.as_ref().len() as u16).to_be_bytes());
+    digest.update(unblinded_element.as_ref());
+    digest.update("Finalize");
+    digest.finalize()
+}
 
-    (m, z)
+/// Expand a PRF output into `out.len()` bytes bound to the provided `label`.
+///
+/// This is not defined in RFC 9497. Each block of output is computed as
+/// `Hash(I2OSP(len(secret), 2) || secret || I2OSP(len(label), 2) || label || I2OSP(i, 2) || "Expand")`.
+pub(crate) fn expand<S: Suite>(secret: &[u8], label: &[u8], out: &mut [u8]) {
+    for (i, block) in out
+        .chunks_mut(<S::Hash as Digest>::output_size())
+        .enumerate()
+    {
+        let digest = S::Hash::new()
+            .chain_update((secret.len() as u16).to_be_bytes())
+            .chain_update(secret)
+            .chain_update((label.len() as u16).to_be_bytes())
+            .chain_update(label)
+            .chain_update((i as u16).to_be_bytes())
+            .chain_update("Expand")
+            .finalize();
+        block.copy_from_slice(&digest[..block.len()]);
+    }
 }
 
-/// [`CreateContextString`] in RFC 9497, with a prefix.
+/// [`CreateContextString`] in RFC 9497, with a prefix, and followed by the length prefixed
+/// [`Suite::DOMAIN`] if it is not empty.
 ///
 /// [`CreateContextString`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.1-5
 macro_rules! context_string {
@@ -345,20 +672,80 @@ macro_rules! context_string {
         [
             $prefix,
             b"OPRFV1-",
-            &[<$mode>::IDENTIFIER],
+            &[<$mode>::IDENTIFIER.0],
             b"-",
             <$suite>::IDENTIFIER,
+            if <$suite>::DOMAIN.is_empty() {
+                b""
+            } else {
+                b"-App-"
+            },
+            if <$suite>::DOMAIN.is_empty() {
+                &[]
+            } else {
+                &const {
+                    assert!(
+                        <$suite>::DOMAIN.len() <= u16::MAX as usize,
+                        "the domain is shorter than 2^16 bytes"
+                    );
+                    (<$suite>::DOMAIN.len() as u16).to_be_bytes()
+                }
+            },
+            <$suite>::DOMAIN,
         ]
     };
 }
 pub(crate) use context_string;
 
+/// Compute `[f(0), ..., f(N - 1)]`, in parallel with the `rayon` feature.
+///
+/// The result is the same with and without `rayon`, as long as `f` does not rely on the order of
+/// the calls.
+fn map_array<T: Send, const N: usize>(f: impl Fn(usize) -> T + Send + Sync) -> [T; N] {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        let items: alloc::vec::Vec<T> = (0..N).into_par_iter().map(f).collect();
+        match items.try_into() {
+            Ok(items) => items,
+            Err(_) => unreachable!("`N` items are collected"),
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    core::array::from_fn(f)
+}
+
 /// Helper for hashing to a group with the appropriate domain.
 fn hash_to_group<S: Suite, M: Mode>(hash: &[&[u8]]) -> S::Group {
     S::hash_to_group(hash, &context_string!(b"HashToGroup-"; <S, M>))
 }
 
-/// Helper for hashing to a group with the appropriate domain.
+/// Hash the `inputs` to the group with the appropriate domain, rejecting the identity.
+fn hash_inputs<const N: usize, S: Suite, M: Mode>(
+    inputs: [Input<'_>; N],
+) -> Result<[S::Group; N], client::InvalidInput> {
+    let input_elements: [S::Group; N] = map_array(|i| hash_to_group::<S, M>(&[inputs[i].as_ref()]));
+    if input_elements.iter().any(|e| e.is_identity().into()) {
+        return Err(client::InvalidInput);
+    }
+
+    Ok(input_elements)
+}
+
+/// Helper for hashing to a scalar with the appropriate domain.
 fn hash_to_scalar<S: Suite, M: Mode>(hash: &[&[u8]]) -> <S::Group as Group>::Scalar {
     S::hash_to_scalar(hash, &context_string!(b"HashToScalar-"; <S, M>))
 }
+
+/// Helper for hashing the shared `info` of the `mode::Partial` mode to a scalar.
+///
+/// Specified in [RFC 9497 Section 3.3.3](https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.3-2).
+fn info_scalar<S: Suite>(info: Input<'_>) -> <S::Group as Group>::Scalar {
+    let framed_info = [
+        b"Info".as_slice(),
+        &(info.as_ref().len() as u16).to_be_bytes(),
+        info.as_ref(),
+    ];
+    hash_to_scalar::<S, mode::Partial>(&framed_info)
+}
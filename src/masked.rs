@@ -0,0 +1,305 @@
+//! Server evaluation hardened against side-channel and fault attacks.
+//!
+//! A [`MaskedServer`] never holds its secret key `k` in one piece: it keeps an additive split
+//! `k = k0 + k1`, and re-randomizes the split before every evaluation. Multiplications by the key
+//! are done share by share, so the intermediate values of two evaluations are unrelated and their
+//! power or EM traces don't align. In the [`Partial`] mode, the tweaked key `t = k + m` is
+//! inverted with multiplicative blinding, and its inverse is applied as the product of two
+//! random-looking scalars. The nonces of the proofs are derived with a random nonce key, drawn
+//! when masking, so the key is never hashed.
+//!
+//! Before releasing [`Evaluated`] elements, the server also checks them against fault injection:
+//! - the shares must still add up to the key of the [`VerifyingKey`],
+//! - in the [`Verifiable`] and [`Partial`] modes, the server verifies its own proof, which fails
+//!   if an evaluated element or the proof was corrupted,
+//! - in the [`Base`] mode, the server evaluates a second time with a fresh split, and compares
+//!   the results.
+//!
+//! The evaluated elements are the same as the ones of [`Server::evaluate`], and the proofs are
+//! verified in the same way. This is not defined in RFC 9497.
+//!
+//! Masking only helps if the [`Suite`] implements the group and scalar arithmetic in constant
+//! time.
+//!
+//! [`Server::evaluate`]: Server#method.evaluate
+
+use group::{Group, ff::Field};
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey, challenge, composite_seed,
+    compute_composites, derive_nonce, info_scalar, map_array,
+    mode::{Base, Mode, Partial, ServerPayload, Verifiable},
+    server::{SecretKey, Server, UndefinedInverse},
+    verify_proof_with_seed,
+};
+
+/// Server of the OPRF protocol, with its secret key masked.
+///
+/// Created from a [`Server`] with [`MaskedServer::new`]. Every evaluation takes `&mut self`, as
+/// it re-randomizes the shares of the secret key.
+pub struct MaskedServer<S: Suite, M: Mode> {
+    shares: [SecretKey<<S::Group as Group>::Scalar>; 2],
+    /// The key the nonces of the proofs are derived with, independent of the secret key.
+    nonce_key: SecretKey<<S::Group as Group>::Scalar>,
+    verifying_key: VerifyingKey<S::Group>,
+    payload: M::ServerPayload<S>,
+}
+
+impl<S: Suite, M: Mode> core::fmt::Debug for MaskedServer<S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MaskedServer")
+            .field("verifying_key", &self.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Suite, M: Mode> zeroize::Zeroize for MaskedServer<S, M> {
+    fn zeroize(&mut self) {
+        self.shares.iter_mut().for_each(zeroize::Zeroize::zeroize);
+        zeroize::Zeroize::zeroize(&mut self.nonce_key);
+    }
+}
+
+impl<S: Suite, M: Mode> zeroize::ZeroizeOnDrop for MaskedServer<S, M> {}
+
+impl<S: Suite, M: Mode> MaskedServer<S, M> {
+    /// Mask the secret key of the `server`.
+    pub fn new(server: Server<S, M>, rng: &mut impl RngCore) -> Self {
+        let (key, payload) = server.into_parts();
+        let verifying_key = payload
+            .verifying_key()
+            .unwrap_or_else(|| VerifyingKey(S::Group::mul_by_generator(key.expose_secret())));
+        let mask = <S::Group as Group>::Scalar::random(rng);
+
+        MaskedServer {
+            shares: [
+                SecretKey::new(*key.expose_secret() - mask),
+                SecretKey::new(mask),
+            ],
+            nonce_key: SecretKey::new(<S::Group as Group>::Scalar::random(rng)),
+            verifying_key,
+            payload,
+        }
+    }
+
+    /// The verifying key of the server.
+    pub fn verifying_key(&self) -> VerifyingKey<S::Group> {
+        self.verifying_key
+    }
+
+    /// Re-randomize the split of the secret key, and check that it still matches the verifying
+    /// key.
+    fn refresh(&mut self, rng: &mut impl RngCore) -> Result<(), FaultDetected> {
+        let mask = <S::Group as Group>::Scalar::random(rng);
+        let [k0, k1] = &self.shares;
+        self.shares = [
+            SecretKey::new(*k0.expose_secret() + mask),
+            SecretKey::new(*k1.expose_secret() - mask),
+        ];
+
+        let [k0, k1] = &self.shares;
+        let verifying_key = S::Group::mul_by_generator(k0.expose_secret())
+            + S::Group::mul_by_generator(k1.expose_secret());
+        if verifying_key != self.verifying_key.0 {
+            return Err(FaultDetected);
+        }
+
+        Ok(())
+    }
+
+    /// Multiply the blinded elements by the secret key, share by share.
+    fn multiply<const N: usize>(
+        &self,
+        blinded_elements: &[Blinded<S::Group>; N],
+    ) -> [Evaluated<S::Group>; N] {
+        let [k0, k1] = &self.shares;
+        let (k0, k1) = (*k0.expose_secret(), *k1.expose_secret());
+        map_array(|i| Evaluated(blinded_elements[i].0 * k0 + blinded_elements[i].0 * k1))
+    }
+
+    /// Prove the evaluation for the key `k0 + k1 + m`, with `b = G * (k0 + k1 + m)`.
+    ///
+    /// The composites are computed from the evaluation only, so that the key is only used
+    /// share by share to compute the response. The nonce is hedged as in
+    /// [`Server::evaluate_hedged`](Server#method.evaluate_hedged), but derived with the nonce key
+    /// instead of the secret key, so that neither the key nor its shares are ever hashed. A weak
+    /// `rng` still does not leak the key, as long as the nonce key is secret.
+    fn prove<P: Mode>(
+        &self,
+        m: <S::Group as Group>::Scalar,
+        b: S::Group,
+        seed: &digest::Output<S::Hash>,
+        c: &[S::Group],
+        d: &[S::Group],
+        rng: &mut impl RngCore,
+    ) -> Result<Proof<<S::Group as Group>::Scalar>, FaultDetected> {
+        let (composite_m, composite_z) = compute_composites::<S, P>(seed, c, d);
+
+        let mut randomness = [0; 32];
+        rng.fill_bytes(&mut randomness);
+        let r = derive_nonce::<S, P>(
+            *self.nonce_key.expose_secret(),
+            composite_m,
+            composite_z,
+            &randomness,
+        );
+        let [k0, k1] = &self.shares;
+        let t2 = S::Group::mul_by_generator(&r);
+        let t3 = composite_m * r;
+
+        let challenge = challenge::<S, P>(b, composite_m, composite_z, t2, t3);
+        let s = r - challenge * k0.expose_secret() - challenge * k1.expose_secret() - challenge * m;
+        let proof = Proof { c: challenge, s };
+
+        if !verify_proof_with_seed::<S, P>(S::Group::generator(), b, seed, c, d, proof) {
+            return Err(FaultDetected);
+        }
+
+        Ok(proof)
+    }
+}
+
+impl<S: Suite> MaskedServer<S, Base> {
+    /// Evaluate the blinded elements.
+    ///
+    /// The elements are evaluated twice, with different splits of the secret key, and only
+    /// released if both evaluations agree.
+    pub fn evaluate<const N: usize>(
+        &mut self,
+        blinded_elements: [Blinded<S::Group>; N],
+        rng: &mut impl RngCore,
+    ) -> Result<[Evaluated<S::Group>; N], FaultDetected> {
+        self.refresh(rng)?;
+        let evaluated_elements = self.multiply(&blinded_elements);
+        self.refresh(rng)?;
+        if self.multiply(&blinded_elements) != evaluated_elements {
+            return Err(FaultDetected);
+        }
+
+        Ok(evaluated_elements)
+    }
+}
+
+impl<S: Suite> MaskedServer<S, Verifiable> {
+    /// Evaluate the blinded elements and prove the evaluation.
+    ///
+    /// The proof is verified before the evaluated elements are released.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate<const N: usize>(
+        &mut self,
+        blinded_elements: [Blinded<S::Group>; N],
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), FaultDetected> {
+        self.refresh(rng)?;
+        let evaluated_elements = self.multiply(&blinded_elements);
+        let proof = self.prove::<Verifiable>(
+            <S::Group as Group>::Scalar::ZERO,
+            self.verifying_key.0,
+            &self.payload.seed,
+            &blinded_elements.map(|Blinded(b)| b),
+            &evaluated_elements.map(|Evaluated(e)| e),
+            rng,
+        )?;
+
+        Ok((evaluated_elements, proof))
+    }
+}
+
+impl<S: Suite> MaskedServer<S, Partial> {
+    /// Evaluate the partially blinded elements and prove the evaluation.
+    ///
+    /// The proof is verified before the evaluated elements are released.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate<const N: usize>(
+        &mut self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), MaskedEvaluationError>
+    {
+        self.refresh(rng)?;
+        let m = info_scalar::<S>(info);
+
+        // `t * r` is zero exactly when `t` is, as `r` is not.
+        let r = loop {
+            let r = <S::Group as Group>::Scalar::random(&mut *rng);
+            if !bool::from(r.is_zero()) {
+                break r;
+            }
+        };
+        let [k0, k1] = &self.shares;
+        let masked_t = *k0.expose_secret() * r + *k1.expose_secret() * r + m * r;
+        let masked_t_inv = masked_t.invert().into_option().ok_or(UndefinedInverse)?;
+        let evaluated_elements: [Evaluated<S::Group>; N] =
+            map_array(|i| Evaluated(blinded_elements[i].0 * r * masked_t_inv));
+
+        let tweaked_key = S::Group::mul_by_generator(&m) + self.verifying_key.0;
+        let proof = self.prove::<Partial>(
+            m,
+            tweaked_key,
+            &composite_seed::<S, Partial>(tweaked_key),
+            &evaluated_elements.map(|Evaluated(e)| e),
+            &blinded_elements.map(|Blinded(b)| b),
+            rng,
+        )?;
+
+        Ok((evaluated_elements, proof))
+    }
+}
+
+/// A fault was detected during a masked evaluation.
+///
+/// The evaluated elements were not released. If this persists, the shares of the secret key were
+/// corrupted, and the [`MaskedServer`] must be recreated from the secret key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FaultDetected;
+
+impl core::fmt::Display for FaultDetected {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "a fault was detected during the evaluation")
+    }
+}
+
+impl core::error::Error for FaultDetected {}
+
+/// Error returned by [`MaskedServer<_, Partial>::evaluate`][Partial].
+///
+/// [Partial]: MaskedServer#impl-MaskedServer<S,+Partial>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MaskedEvaluationError {
+    /// The server's secret key combined with the `info` results in a zero scalar.
+    UndefinedInverse(UndefinedInverse),
+    /// A fault was detected during the evaluation.
+    FaultDetected(FaultDetected),
+}
+
+impl From<UndefinedInverse> for MaskedEvaluationError {
+    fn from(error: UndefinedInverse) -> Self {
+        MaskedEvaluationError::UndefinedInverse(error)
+    }
+}
+
+impl From<FaultDetected> for MaskedEvaluationError {
+    fn from(error: FaultDetected) -> Self {
+        MaskedEvaluationError::FaultDetected(error)
+    }
+}
+
+impl core::fmt::Display for MaskedEvaluationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MaskedEvaluationError::UndefinedInverse(e) => e.fmt(f),
+            MaskedEvaluationError::FaultDetected(e) => e.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for MaskedEvaluationError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            MaskedEvaluationError::UndefinedInverse(e) => Some(e),
+            MaskedEvaluationError::FaultDetected(e) => Some(e),
+        }
+    }
+}
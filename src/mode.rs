@@ -5,9 +5,12 @@
 //! provided as a type parameter to these structs, and the correct methods are exposed based
 //! on it.
 
+use core::marker::PhantomData;
+
+use digest::Output;
 use group::Group;
 
-use crate::{Blinded, Input, VerifyingKey};
+use crate::{Blinded, Input, Suite, VerifyingKey, composite_seed};
 
 /// The basic (`OPRF`) mode of operation.
 ///
@@ -37,89 +40,226 @@ pub struct Verifiable;
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Partial;
 
-/// Helper trait for the different modes of OPRF operation.
-pub(crate) trait Mode {
+/// A mode of operation of the OPRF protocol.
+///
+/// The modes of RFC 9497 are [`Base`], [`Verifiable`] and [`Partial`]. Applications can define
+/// their own modes by implementing this trait on their own zero sized type. The identifier of the
+/// mode is part of the context string, so it separates the domains of the hash functions, and the
+/// keys derived with [`Server::new`](crate::server::Server::new), from those of other modes. A
+/// custom mode gets its [`Identifier`] from [`Identifier::custom`], which rejects the identifiers
+/// of RFC 9497.
+///
+/// The protocol methods of [`Client`](crate::client::Client) and
+/// [`Server`](crate::server::Server) are only implemented for the modes of RFC 9497, the
+/// [`ClientMode`]s. In a custom mode, the server manages its keys, and the application runs the
+/// protocol with the building blocks of [`primitives`](crate::primitives).
+pub trait Mode {
     /// The identifier for this mode.
-    const IDENTIFIER: u8;
+    const IDENTIFIER: Identifier<Self>;
+
+    /// Extra payload the server needs to run the protocol in this mode.
+    type ServerPayload<S: Suite>: ServerPayload<S>;
+}
+
+/// The identifier of the mode `M`, in its context string.
+///
+/// The identifiers `0x00`, `0x01` and `0x02` are those of the modes of RFC 9497, and only
+/// [`Base`], [`Verifiable`] and [`Partial`] have them. The identifier of a mode can't be reused for
+/// another one.
+pub struct Identifier<M: ?Sized>(pub(crate) u8, PhantomData<fn() -> M>);
+
+impl<M: ?Sized> Clone for Identifier<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: ?Sized> Copy for Identifier<M> {}
+
+impl<M: ?Sized> core::fmt::Debug for Identifier<M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Identifier").field(&self.0).finish()
+    }
+}
 
+impl<M: ?Sized> Identifier<M> {
+    /// The identifier of a custom mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `identifier` is one of the modes of RFC 9497, i.e. fails to compile when used
+    /// to define [`Mode::IDENTIFIER`].
+    pub const fn custom(identifier: u8) -> Self {
+        assert!(
+            identifier > 0x02,
+            "custom modes must not use the identifiers 0x00, 0x01 and 0x02 of RFC 9497"
+        );
+        Identifier(identifier, PhantomData)
+    }
+
+    /// The byte of the identifier.
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// A mode of RFC 9497, in which the [`Client`](crate::client::Client) runs the protocol.
+///
+/// This trait is sealed.
+pub trait ClientMode: Mode + sealed::Sealed {
     /// Extra payload the client needs to run the protocol in this mode.
     type ClientPayload<'a, const N: usize, E>;
-    type ServerPayload<E: Group>: for<'a> _From<&'a E::Scalar> + GetVerifyingKey<E>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Base {}
+    impl Sealed for super::Verifiable {}
+    impl Sealed for super::Partial {}
 }
 
 impl Mode for Base {
-    const IDENTIFIER: u8 = 0x00;
+    const IDENTIFIER: Identifier<Self> = Identifier(0x00, PhantomData);
+
+    type ServerPayload<S: Suite> = Empty;
+}
 
+impl ClientMode for Base {
     type ClientPayload<'a, const N: usize, E> = ();
-    type ServerPayload<E: Group> = Empty;
 }
 
 impl Mode for Verifiable {
-    const IDENTIFIER: u8 = 0x01;
+    const IDENTIFIER: Identifier<Self> = Identifier(0x01, PhantomData);
+
+    type ServerPayload<S: Suite> = VerifiableServerPayload<S>;
+}
 
+impl ClientMode for Verifiable {
     type ClientPayload<'a, const N: usize, E> = VerifyingPayload<N, E>;
-    type ServerPayload<E: Group> = VerifyingKey<E>;
 }
 
 impl Mode for Partial {
-    const IDENTIFIER: u8 = 0x02;
+    const IDENTIFIER: Identifier<Self> = Identifier(0x02, PhantomData);
 
-    type ClientPayload<'a, const N: usize, E> = PartialPayload<'a, N, E>;
-    type ServerPayload<E: Group> = VerifyingKey<E>;
+    type ServerPayload<S: Suite> = VerifyingKey<S::Group>;
 }
 
-/// Helper `From` trait that does not have a blanket impl.
-pub(crate) trait _From<E> {
-    fn _from(e: E) -> Self;
+impl ClientMode for Partial {
+    type ClientPayload<'a, const N: usize, E> = PartialPayload<'a, N, E>;
 }
 
-/// Helper to try to get a verifying key from the server payload.
-pub(crate) trait GetVerifyingKey<E> {
-    fn get_verifying_key(&self) -> Option<VerifyingKey<E>>;
+/// The extra payload a [`Server`](crate::server::Server) holds in a [`Mode`], computed from its
+/// verifying key.
+pub trait ServerPayload<S: Suite> {
+    /// Compute the payload of the server with the `verifying_key`.
+    fn from_verifying_key(verifying_key: VerifyingKey<S::Group>) -> Self;
+
+    /// Compute the payload of the server with the `secret_key`.
+    ///
+    /// The default implementation computes the verifying key. Override it if the payload does not
+    /// need it.
+    fn from_secret_key(secret_key: &<S::Group as Group>::Scalar) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_verifying_key(VerifyingKey(S::Group::mul_by_generator(secret_key)))
+    }
+
+    /// The verifying key of the server, if the payload holds it.
+    ///
+    /// It must be the key the payload was computed with, which is checked in debug builds when the
+    /// server is created. Otherwise, the verifying key is computed from the secret key when needed.
+    fn verifying_key(&self) -> Option<VerifyingKey<S::Group>>;
 }
 
 /// Extra payload the client needs to run the protocol when proof evaluation is needed.
-pub(crate) struct VerifyingPayload<const N: usize, E> {
+pub struct VerifyingPayload<const N: usize, E> {
     /// The verifying_key of the server.
-    pub verifying_key: VerifyingKey<E>,
+    pub(crate) verifying_key: VerifyingKey<E>,
     /// The blinded element.
-    pub blinded_elements: [Blinded<E>; N],
+    pub(crate) blinded_elements: [Blinded<E>; N],
 }
 
 /// Extra payload the client needs to run the protocol when proof evaluation with shared info is
 /// needed.
-pub(crate) struct PartialPayload<'a, const N: usize, E> {
+pub struct PartialPayload<'a, const N: usize, E> {
     /// The verifying_key of the server.
-    pub verifying_key: VerifyingKey<E>,
+    pub(crate) verifying_key: VerifyingKey<E>,
     /// The blinded element.
-    pub blinded_elements: [Blinded<E>; N],
+    pub(crate) blinded_elements: [Blinded<E>; N],
     /// The shared info.
-    pub info: Input<'a>,
+    pub(crate) info: Input<'a>,
+}
+
+/// Extra payload the server needs to run the protocol in the [`Verifiable`] mode.
+pub struct VerifiableServerPayload<S: Suite> {
+    /// The verifying key of the server.
+    pub(crate) verifying_key: VerifyingKey<S::Group>,
+    /// The seed of the composites of the proofs, which only depends on the verifying key.
+    pub(crate) seed: Output<S::Hash>,
+}
+
+impl<S: Suite> Clone for VerifiableServerPayload<S> {
+    fn clone(&self) -> Self {
+        VerifiableServerPayload {
+            verifying_key: self.verifying_key,
+            seed: self.seed.clone(),
+        }
+    }
+}
+
+impl<S: Suite> core::fmt::Debug for VerifiableServerPayload<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.verifying_key.fmt(f)
+    }
 }
 
-impl<E: Group> _From<&E::Scalar> for VerifyingKey<E> {
-    fn _from(s: &E::Scalar) -> Self {
-        VerifyingKey(E::mul_by_generator(s))
+impl<S: Suite> PartialEq for VerifiableServerPayload<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.verifying_key == other.verifying_key
     }
 }
 
-impl<E: Group> GetVerifyingKey<E> for VerifyingKey<E> {
-    fn get_verifying_key(&self) -> Option<VerifyingKey<E>> {
+impl<S: Suite> Eq for VerifiableServerPayload<S> {}
+
+impl<S: Suite> ServerPayload<S> for VerifiableServerPayload<S> {
+    fn from_verifying_key(verifying_key: VerifyingKey<S::Group>) -> Self {
+        VerifiableServerPayload {
+            verifying_key,
+            seed: composite_seed::<S, Verifiable>(verifying_key.0),
+        }
+    }
+
+    fn verifying_key(&self) -> Option<VerifyingKey<S::Group>> {
+        Some(self.verifying_key)
+    }
+}
+
+impl<S: Suite> ServerPayload<S> for VerifyingKey<S::Group> {
+    fn from_verifying_key(verifying_key: VerifyingKey<S::Group>) -> Self {
+        verifying_key
+    }
+
+    fn verifying_key(&self) -> Option<VerifyingKey<S::Group>> {
         Some(*self)
     }
 }
 
 /// Empty payload.
-pub(crate) struct Empty;
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Empty;
 
-impl<T> _From<&T> for Empty {
-    fn _from(_: &T) -> Self {
+impl<S: Suite> ServerPayload<S> for Empty {
+    fn from_verifying_key(_: VerifyingKey<S::Group>) -> Self {
+        Empty
+    }
+
+    fn from_secret_key(_: &<S::Group as Group>::Scalar) -> Self {
         Empty
     }
-}
 
-impl<E> GetVerifyingKey<E> for Empty {
-    fn get_verifying_key(&self) -> Option<VerifyingKey<E>> {
+    fn verifying_key(&self) -> Option<VerifyingKey<S::Group>> {
         None
     }
 }
@@ -0,0 +1,166 @@
+//! Generic variable-time scalar multiplication.
+//!
+//! This contains the default implementation of
+//! [`Suite::multiscalar_mul`](crate::Suite::multiscalar_mul), and the fixed-base tables of
+//! [`PreparedVerifyingKey`](crate::prepared::PreparedVerifyingKey). None of it runs in constant
+//! time, so it is only used on public values.
+
+use group::{Group, ff::PrimeField};
+
+/// Number of elements processed together by [`straus`].
+const STRAUS_ELEMENTS: usize = 8;
+/// Width in bits of the windows of [`straus`].
+const STRAUS_WINDOW: usize = 4;
+/// Number of elements from which [`pippenger`] is used instead of [`straus`].
+#[cfg(feature = "alloc")]
+const PIPPENGER_THRESHOLD: usize = 64;
+
+/// Compute the sum of `scalars[i] * elements[i]`.
+pub(crate) fn multiscalar_mul<G: Group>(scalars: &[G::Scalar], elements: &[G]) -> G {
+    debug_assert_eq!(scalars.len(), elements.len());
+    #[cfg(feature = "alloc")]
+    if elements.len() >= PIPPENGER_THRESHOLD {
+        return pippenger(scalars, elements);
+    }
+
+    scalars
+        .chunks(STRAUS_ELEMENTS)
+        .zip(elements.chunks(STRAUS_ELEMENTS))
+        .fold(G::identity(), |acc, (scalars, elements)| {
+            acc + straus(scalars, elements)
+        })
+}
+
+/// Straus' method with fixed windows, for at most [`STRAUS_ELEMENTS`] elements.
+fn straus<G: Group>(scalars: &[G::Scalar], elements: &[G]) -> G {
+    let mut tables = [[G::identity(); 1 << STRAUS_WINDOW]; STRAUS_ELEMENTS];
+    let mut scalar_bits = [<G::Scalar as PrimeField>::Repr::default(); STRAUS_ELEMENTS];
+    for ((table, scalar_bits), (scalar, element)) in tables
+        .iter_mut()
+        .zip(&mut scalar_bits)
+        .zip(scalars.iter().zip(elements))
+    {
+        for i in 1..table.len() {
+            table[i] = table[i - 1] + element;
+        }
+        *scalar_bits = bits(*scalar);
+    }
+
+    let mut acc = G::identity();
+    for window in (0..(G::Scalar::NUM_BITS as usize).div_ceil(STRAUS_WINDOW)).rev() {
+        for _ in 0..STRAUS_WINDOW {
+            acc = acc.double();
+        }
+        for (table, scalar_bits) in tables.iter().zip(&scalar_bits).take(elements.len()) {
+            acc += table[digit(scalar_bits.as_ref(), window * STRAUS_WINDOW, STRAUS_WINDOW)];
+        }
+    }
+    acc
+}
+
+/// Pippenger's bucket method, for large numbers of elements.
+#[cfg(feature = "alloc")]
+fn pippenger<G: Group>(scalars: &[G::Scalar], elements: &[G]) -> G {
+    let width = (elements.len().ilog2() as usize)
+        .saturating_sub(2)
+        .clamp(4, 16);
+    let scalar_bits: alloc::vec::Vec<_> = scalars.iter().map(|scalar| bits(*scalar)).collect();
+    let mut buckets = alloc::vec![G::identity(); (1 << width) - 1];
+
+    let mut acc = G::identity();
+    for window in (0..(G::Scalar::NUM_BITS as usize).div_ceil(width)).rev() {
+        for _ in 0..width {
+            acc = acc.double();
+        }
+        buckets.fill(G::identity());
+        for (scalar_bits, element) in scalar_bits.iter().zip(elements) {
+            let digit = digit(scalar_bits.as_ref(), window * width, width);
+            if digit != 0 {
+                buckets[digit - 1] += element;
+            }
+        }
+        let mut running = G::identity();
+        for bucket in buckets.iter().rev() {
+            running += bucket;
+            acc += running;
+        }
+    }
+    acc
+}
+
+/// Width in bits of the windows of [`FixedBase`].
+#[cfg(feature = "alloc")]
+const FIXED_BASE_WINDOW: usize = 4;
+
+/// Precomputed multiples of a fixed base, so that multiplying it only takes additions.
+///
+/// Window `w` of the table holds `j * 2^(4 * w) * base` for every 4-bit digit `j`.
+#[cfg(feature = "alloc")]
+pub(crate) struct FixedBase<G>(alloc::vec::Vec<[G; 1 << FIXED_BASE_WINDOW]>);
+
+#[cfg(feature = "alloc")]
+impl<G: Group> FixedBase<G> {
+    pub(crate) fn new(base: G) -> Self {
+        let windows = (G::Scalar::NUM_BITS as usize).div_ceil(FIXED_BASE_WINDOW);
+        let mut base = base;
+        FixedBase(
+            (0..windows)
+                .map(|_| {
+                    let mut window = [G::identity(); 1 << FIXED_BASE_WINDOW];
+                    for i in 1..window.len() {
+                        window[i] = window[i - 1] + base;
+                    }
+                    base = window[window.len() - 1] + base;
+                    window
+                })
+                .collect(),
+        )
+    }
+
+    /// Multiply the base by `scalar`, in variable time.
+    pub(crate) fn mul_vartime(&self, scalar: &G::Scalar) -> G {
+        let scalar_bits = bits(*scalar);
+        self.0
+            .iter()
+            .enumerate()
+            .fold(G::identity(), |acc, (window, table)| {
+                acc + table[digit(
+                    scalar_bits.as_ref(),
+                    window * FIXED_BASE_WINDOW,
+                    FIXED_BASE_WINDOW,
+                )]
+            })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<G: Copy> Clone for FixedBase<G> {
+    fn clone(&self) -> Self {
+        FixedBase(self.0.clone())
+    }
+}
+
+/// The bits of the canonical integer representation of `scalar`, in little-endian order.
+///
+/// The encoding of [`PrimeField::Repr`] is specific to each field, so the bits are extracted by
+/// halving instead.
+fn bits<F: PrimeField>(mut scalar: F) -> F::Repr {
+    let mut bits = F::Repr::default();
+    for i in 0..F::NUM_BITS as usize {
+        if bool::from(scalar.is_odd()) {
+            bits.as_mut()[i / 8] |= 1 << (i % 8);
+            scalar -= F::ONE;
+        }
+        scalar *= F::TWO_INV;
+    }
+    bits
+}
+
+/// The `width` bits of `bits` starting at bit `start`.
+fn digit(bits: &[u8], start: usize, width: usize) -> usize {
+    (start..start + width)
+        .take_while(|i| i / 8 < bits.len())
+        .fold(0, |digit, i| {
+            digit | (((bits[i / 8] >> (i % 8)) & 1) as usize) << (i - start)
+        })
+}
@@ -0,0 +1,332 @@
+//! Import and export of keys in the PKCS#8, SEC1 and SubjectPublicKeyInfo formats.
+//!
+//! These formats identify keys by the OID of their curve, so they are only supported for the
+//! suites of RFC 9497 over NIST curves: `P256-SHA256`, `P384-SHA384` and `P521-SHA512`. The suite
+//! is recognized by its [`Suite::IDENTIFIER`], and other suites fail with [`UnsupportedSuite`].
+//!
+//! - Secret keys are encoded as SEC1 `ECPrivateKey` structures, either alone, with the PEM label
+//!   `EC PRIVATE KEY`, or wrapped in a PKCS#8 `PrivateKeyInfo`, with the PEM label `PRIVATE KEY`.
+//! - Verifying keys are encoded as `SubjectPublicKeyInfo` structures, with the PEM label
+//!   `PUBLIC KEY`.
+//!
+//! Points are encoded in the compressed form of `SerializeElement` in RFC 9497. Both the compressed
+//! and the uncompressed SEC1 forms are decoded, as most tools, e.g. `openssl ec -pubout`, emit
+//! uncompressed points. When a decoded secret key embeds a public key, it must match the secret
+//! key. Decoded secret keys are used with
+//! [`Server::from_secret_key`](crate::server::Server::from_secret_key).
+
+use alloc::{string::String, vec::Vec};
+
+use crypto_bigint::{NonZero, U256, U384, U576, Uint};
+use group::{
+    Group, GroupEncoding,
+    ff::{Field, PrimeField},
+};
+use pkcs8::{
+    AlgorithmIdentifierRef, LineEnding, ObjectIdentifier, PrivateKeyInfoRef,
+    SubjectPublicKeyInfoRef,
+    der::{
+        Decode, Document, SecretDocument,
+        asn1::{BitStringRef, OctetStringRef},
+    },
+};
+use sec1::{EcParameters, EcPrivateKey};
+use zeroize::Zeroizing;
+
+use crate::{
+    InvalidEncoding, Suite, UnsupportedSuite, VerifyingKey,
+    encoding::{decode_element, decode_scalar},
+    server::SecretKey,
+};
+
+/// `id-ecPublicKey` from RFC 5480.
+const EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+
+/// The OID of the curve of the suite.
+fn curve<S: Suite>() -> Result<ObjectIdentifier, UnsupportedSuite> {
+    match S::IDENTIFIER {
+        b"P256-SHA256" => Ok(ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7")),
+        b"P384-SHA384" => Ok(ObjectIdentifier::new_unwrap("1.3.132.0.34")),
+        b"P521-SHA512" => Ok(ObjectIdentifier::new_unwrap("1.3.132.0.35")),
+        _ => Err(UnsupportedSuite),
+    }
+}
+
+/// Decode a SEC1 point, in the compressed form of `SerializeElement`, or in the uncompressed form.
+///
+/// Uncompressed points are checked to be on the curve, and then compressed.
+fn decode_point<S: Suite>(bytes: &[u8]) -> Result<S::Group, InvalidEncoding> {
+    let mut compressed = <S::Group as GroupEncoding>::Repr::default();
+    let field_len = compressed.as_ref().len() - 1;
+    match bytes.split_first() {
+        Some((0x04, coordinates)) if coordinates.len() == 2 * field_len => {
+            let (x, y) = coordinates.split_at(field_len);
+            if !on_curve::<S>(x, y) {
+                return Err(InvalidEncoding);
+            }
+            let repr = compressed.as_mut();
+            repr[0] = 0x02 | (y[field_len - 1] & 1);
+            repr[1..].copy_from_slice(x);
+            decode_element(compressed.as_ref())
+        }
+        _ => decode_element(bytes),
+    }
+}
+
+/// Whether the big-endian coordinates `(x, y)` are on the curve `y² = x³ - 3x + b` of the suite.
+fn on_curve<S: Suite>(x: &[u8], y: &[u8]) -> bool {
+    match S::IDENTIFIER {
+        b"P256-SHA256" => satisfies_equation(
+            x,
+            y,
+            U256::from_be_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff"),
+            U256::from_be_hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+        ),
+        b"P384-SHA384" => satisfies_equation(
+            x,
+            y,
+            U384::from_be_hex(
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff",
+            ),
+            U384::from_be_hex(
+                "b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef",
+            ),
+        ),
+        b"P521-SHA512" => satisfies_equation(
+            x,
+            y,
+            U576::from_be_hex(
+                "00000000000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            ),
+            U576::from_be_hex(
+                "0000000000000051953eb9618e1c9a1f929a21a0b68540eea2da725b99b315f3b8b489918ef109e156193951ec7e937b1652c0bd3bb1bf073573df883d2c34f1ef451fd46b503f00",
+            ),
+        ),
+        _ => false,
+    }
+}
+
+/// Whether `y² = x³ - 3x + b` modulo `p`, with `x` and `y` reduced.
+///
+/// Points are public, so this does not need to run in constant time.
+fn satisfies_equation<const LIMBS: usize>(
+    x: &[u8],
+    y: &[u8],
+    p: Uint<LIMBS>,
+    b: Uint<LIMBS>,
+) -> bool {
+    let (Some(x), Some(y)) = (uint::<LIMBS>(x), uint::<LIMBS>(y)) else {
+        return false;
+    };
+    if x >= p || y >= p {
+        return false;
+    }
+    let p = NonZero::<Uint<LIMBS>>::new_unwrap(p);
+    let x3 = x.mul_mod_vartime(&x, &p).mul_mod_vartime(&x, &p);
+    let three_x = x.add_mod(&x, &p).add_mod(&x, &p);
+    y.mul_mod_vartime(&y, &p) == x3.sub_mod(&three_x, &p).add_mod(&b, &p)
+}
+
+/// Decode a big-endian integer of at most `Uint::<LIMBS>::BYTES` bytes.
+fn uint<const LIMBS: usize>(bytes: &[u8]) -> Option<Uint<LIMBS>> {
+    let mut padded = [0; U576::BYTES];
+    let offset = Uint::<LIMBS>::BYTES.checked_sub(bytes.len())?;
+    let padded = &mut padded[..Uint::<LIMBS>::BYTES];
+    padded[offset..].copy_from_slice(bytes);
+    Some(Uint::from_be_slice(padded))
+}
+
+/// Encode the SEC1 `ECPrivateKey` of `key`, with the curve parameters if `parameters` is set.
+fn ec_private_key<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+    parameters: bool,
+) -> Result<SecretDocument, UnsupportedSuite> {
+    let curve = curve::<S>()?;
+    let mut private_key = key.expose_secret().to_repr();
+    let public_key = S::Group::mul_by_generator(key.expose_secret()).to_bytes();
+
+    let ec_private_key = EcPrivateKey {
+        private_key: private_key.as_ref(),
+        parameters: parameters.then_some(EcParameters::NamedCurve(curve)),
+        public_key: Some(public_key.as_ref()),
+    };
+    let document = SecretDocument::try_from(ec_private_key).expect("the key is DER encodable");
+    zeroize::Zeroize::zeroize(private_key.as_mut());
+    Ok(document)
+}
+
+/// Decode the SEC1 `ECPrivateKey` in `der`, for a key of the `curve`.
+fn from_ec_private_key<S: Suite>(
+    der: &[u8],
+    curve: ObjectIdentifier,
+) -> Result<SecretKey<<S::Group as Group>::Scalar>, InvalidEncoding> {
+    let ec_private_key = EcPrivateKey::from_der(der).map_err(|_| InvalidEncoding)?;
+    if let Some(EcParameters::NamedCurve(parameters)) = ec_private_key.parameters
+        && parameters != curve
+    {
+        return Err(InvalidEncoding);
+    }
+
+    let scalar: <S::Group as Group>::Scalar = decode_scalar(ec_private_key.private_key)?;
+    let key = SecretKey::new(scalar);
+    if bool::from(key.expose_secret().is_zero()) {
+        return Err(InvalidEncoding);
+    }
+    if let Some(public_key) = ec_private_key.public_key
+        && decode_point::<S>(public_key)? != S::Group::mul_by_generator(key.expose_secret())
+    {
+        return Err(InvalidEncoding);
+    }
+
+    Ok(key)
+}
+
+/// Encode the secret `key` as a SEC1 `ECPrivateKey` in DER.
+pub fn to_sec1_der<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+) -> Result<Zeroizing<Vec<u8>>, UnsupportedSuite> {
+    Ok(ec_private_key::<S>(key, true)?.to_bytes())
+}
+
+/// Encode the secret `key` as a SEC1 `ECPrivateKey` in PEM.
+pub fn to_sec1_pem<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+) -> Result<Zeroizing<String>, UnsupportedSuite> {
+    Ok(ec_private_key::<S>(key, true)?
+        .to_pem("EC PRIVATE KEY", LineEnding::LF)
+        .expect("the key is PEM encodable"))
+}
+
+/// Decode a secret key from a SEC1 `ECPrivateKey` in DER.
+pub fn from_sec1_der<S: Suite>(
+    der: &[u8],
+) -> Result<SecretKey<<S::Group as Group>::Scalar>, InvalidEncoding> {
+    from_ec_private_key::<S>(der, curve::<S>().map_err(|_| InvalidEncoding)?)
+}
+
+/// Decode a secret key from a SEC1 `ECPrivateKey` in PEM.
+pub fn from_sec1_pem<S: Suite>(
+    pem: &str,
+) -> Result<SecretKey<<S::Group as Group>::Scalar>, InvalidEncoding> {
+    let (label, document) = SecretDocument::from_pem(pem).map_err(|_| InvalidEncoding)?;
+    if label != "EC PRIVATE KEY" {
+        return Err(InvalidEncoding);
+    }
+    from_sec1_der::<S>(document.as_bytes())
+}
+
+/// Encode the secret `key` as a PKCS#8 `PrivateKeyInfo` in DER.
+pub fn to_pkcs8_der<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+) -> Result<Zeroizing<Vec<u8>>, UnsupportedSuite> {
+    Ok(private_key_info::<S>(key)?.to_bytes())
+}
+
+/// Encode the secret `key` as a PKCS#8 `PrivateKeyInfo` in PEM.
+pub fn to_pkcs8_pem<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+) -> Result<Zeroizing<String>, UnsupportedSuite> {
+    Ok(private_key_info::<S>(key)?
+        .to_pem("PRIVATE KEY", LineEnding::LF)
+        .expect("the key is PEM encodable"))
+}
+
+/// Decode a secret key from a PKCS#8 `PrivateKeyInfo` in DER.
+pub fn from_pkcs8_der<S: Suite>(
+    der: &[u8],
+) -> Result<SecretKey<<S::Group as Group>::Scalar>, InvalidEncoding> {
+    let curve = curve::<S>().map_err(|_| InvalidEncoding)?;
+    let private_key_info = PrivateKeyInfoRef::from_der(der).map_err(|_| InvalidEncoding)?;
+    private_key_info
+        .algorithm
+        .assert_oids(EC_PUBLIC_KEY, curve)
+        .map_err(|_| InvalidEncoding)?;
+    from_ec_private_key::<S>(private_key_info.private_key.as_bytes(), curve)
+}
+
+/// Decode a secret key from a PKCS#8 `PrivateKeyInfo` in PEM.
+pub fn from_pkcs8_pem<S: Suite>(
+    pem: &str,
+) -> Result<SecretKey<<S::Group as Group>::Scalar>, InvalidEncoding> {
+    let (label, document) = SecretDocument::from_pem(pem).map_err(|_| InvalidEncoding)?;
+    if label != "PRIVATE KEY" {
+        return Err(InvalidEncoding);
+    }
+    from_pkcs8_der::<S>(document.as_bytes())
+}
+
+fn private_key_info<S: Suite>(
+    key: &SecretKey<<S::Group as Group>::Scalar>,
+) -> Result<SecretDocument, UnsupportedSuite> {
+    let curve = curve::<S>()?;
+    // The parameters are in the algorithm identifier, as recommended by RFC 5915.
+    let ec_private_key = ec_private_key::<S>(key, false)?;
+    let private_key_info = PrivateKeyInfoRef::new(
+        AlgorithmIdentifierRef {
+            oid: EC_PUBLIC_KEY,
+            parameters: Some((&curve).into()),
+        },
+        OctetStringRef::new(ec_private_key.as_bytes()).expect("the key is DER encodable"),
+    );
+    Ok(SecretDocument::encode_msg(&private_key_info).expect("the key is DER encodable"))
+}
+
+/// Encode the `verifying_key` as a `SubjectPublicKeyInfo` in DER.
+pub fn to_public_key_der<S: Suite>(
+    verifying_key: VerifyingKey<S::Group>,
+) -> Result<Vec<u8>, UnsupportedSuite> {
+    Ok(subject_public_key_info::<S>(verifying_key)?.into_vec())
+}
+
+/// Encode the `verifying_key` as a `SubjectPublicKeyInfo` in PEM.
+pub fn to_public_key_pem<S: Suite>(
+    verifying_key: VerifyingKey<S::Group>,
+) -> Result<String, UnsupportedSuite> {
+    Ok(subject_public_key_info::<S>(verifying_key)?
+        .to_pem("PUBLIC KEY", LineEnding::LF)
+        .expect("the key is PEM encodable"))
+}
+
+/// Decode a verifying key from a `SubjectPublicKeyInfo` in DER.
+pub fn from_public_key_der<S: Suite>(
+    der: &[u8],
+) -> Result<VerifyingKey<S::Group>, InvalidEncoding> {
+    let curve = curve::<S>().map_err(|_| InvalidEncoding)?;
+    let subject_public_key_info =
+        SubjectPublicKeyInfoRef::from_der(der).map_err(|_| InvalidEncoding)?;
+    subject_public_key_info
+        .algorithm
+        .assert_oids(EC_PUBLIC_KEY, curve)
+        .map_err(|_| InvalidEncoding)?;
+    let public_key = subject_public_key_info
+        .subject_public_key
+        .as_bytes()
+        .ok_or(InvalidEncoding)?;
+
+    Ok(VerifyingKey(decode_point::<S>(public_key)?))
+}
+
+/// Decode a verifying key from a `SubjectPublicKeyInfo` in PEM.
+pub fn from_public_key_pem<S: Suite>(pem: &str) -> Result<VerifyingKey<S::Group>, InvalidEncoding> {
+    let (label, document) = Document::from_pem(pem).map_err(|_| InvalidEncoding)?;
+    if label != "PUBLIC KEY" {
+        return Err(InvalidEncoding);
+    }
+    from_public_key_der::<S>(document.as_bytes())
+}
+
+fn subject_public_key_info<S: Suite>(
+    verifying_key: VerifyingKey<S::Group>,
+) -> Result<Document, UnsupportedSuite> {
+    let curve = curve::<S>()?;
+    let public_key = verifying_key.0.to_bytes();
+    let subject_public_key_info = SubjectPublicKeyInfoRef {
+        algorithm: AlgorithmIdentifierRef {
+            oid: EC_PUBLIC_KEY,
+            parameters: Some((&curve).into()),
+        },
+        subject_public_key: BitStringRef::from_bytes(public_key.as_ref())
+            .expect("the key is DER encodable"),
+    };
+    Ok(Document::encode_msg(&subject_public_key_info).expect("the key is DER encodable"))
+}
@@ -0,0 +1,133 @@
+//! Proofs of possession of the secret key of a server.
+//!
+//! A client learning a [`VerifyingKey`] for the first time has no assurance that the server it
+//! talks to actually holds the matching secret key: a verifying key can be copied from another
+//! server, or chosen as a combination of other keys. With [`Server::prove_possession`], the server
+//! proves knowledge of its secret key with a Schnorr proof bound to a `context`, which the client
+//! checks with [`verify_possession`] before trusting the key.
+//!
+//! There are two ways to use these proofs:
+//! - Challenge-response, at first contact: the client sends a fresh random `context`, and the
+//!   server answers with [`Server::prove_possession`]. A valid proof shows that the server holds
+//!   the secret key *now*, as it can't be copied from another server.
+//! - Publication, e.g. in a [`KeyDirectory`](crate::directory::KeyDirectory): the server publishes
+//!   the deterministic [`Server::static_possession_proof`], which is the proof for the empty
+//!   `context`. It only shows that the key was chosen by someone holding its secret key, which
+//!   rules out keys built as combinations of other keys. It can be replayed by anyone, so it says
+//!   nothing about who serves the key.
+//!
+//! This is not defined in RFC 9497. The challenge is computed with the `HashToScalar` of the suite,
+//! with the context string of the suite and mode as the domain, so a proof for a key in one suite
+//! and mode is not valid for any other.
+
+use digest::{Digest, Output};
+use group::{Group, GroupEncoding};
+use rand_core::RngCore;
+
+use crate::{
+    Input, Proof, Suite, VerifyingKey, client::InvalidProof, derive_nonce, hash_to_scalar,
+    mode::Mode, server::Server,
+};
+
+impl<S: Suite, M: Mode> Server<S, M> {
+    /// Prove possession of the secret key of the server, for the `context` chosen by the verifier.
+    ///
+    /// The nonce is hedged as in [`Server::evaluate_hedged`](Server#method.evaluate_hedged), with
+    /// randomness drawn from `rng`. The proof is checked with [`verify_possession`] and the same
+    /// `context`.
+    pub fn prove_possession(
+        &self,
+        context: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Proof<<S::Group as Group>::Scalar> {
+        let mut randomness = [0; 32];
+        rng.fill_bytes(&mut randomness);
+        self.prove_possession_with(context, &randomness)
+    }
+
+    /// The deterministic proof of possession of the secret key of the server, for the empty
+    /// `context`.
+    ///
+    /// The same key always gets the same proof, so it can be published alongside the key. See the
+    /// [module documentation](self) for what it guarantees.
+    pub fn static_possession_proof(&self) -> Proof<<S::Group as Group>::Scalar> {
+        self.prove_possession_with(Input(&[]), &[])
+    }
+
+    fn prove_possession_with(
+        &self,
+        context: Input<'_>,
+        randomness: &[u8],
+    ) -> Proof<<S::Group as Group>::Scalar> {
+        let k = *self.secret_key().expose_secret();
+        let verifying_key = self.verifying_key().0;
+        let seed = nonce_seed::<S>(context, randomness);
+        let r = derive_nonce::<S, M>(k, verifying_key, S::Group::generator(), &seed);
+        let c = challenge::<S, M>(verifying_key, S::Group::mul_by_generator(&r), context);
+        let s = r - c * k;
+
+        Proof { c, s }
+    }
+}
+
+/// Verify a proof of possession produced by [`Server::prove_possession`] for the `verifying_key`
+/// and the `context`.
+///
+/// Proofs produced by [`Server::static_possession_proof`] are verified with the empty `context`.
+pub fn verify_possession<S: Suite, M: Mode>(
+    verifying_key: VerifyingKey<S::Group>,
+    context: Input<'_>,
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> Result<(), InvalidProof> {
+    if verifying_key.0.is_identity().into() {
+        return Err(InvalidProof);
+    }
+    let t = S::multiscalar_mul(
+        &[proof.s, proof.c],
+        &[S::Group::generator(), verifying_key.0],
+    );
+    if challenge::<S, M>(verifying_key.0, t, context) != proof.c {
+        return Err(InvalidProof);
+    }
+
+    Ok(())
+}
+
+/// The randomness of the nonce, binding the `context` so that no two statements share a nonce.
+///
+/// Computed as `Hash(I2OSP(len(context), 2) || context || I2OSP(len(randomness), 2) || randomness ||
+/// "PossessionNonce")`.
+fn nonce_seed<S: Suite>(context: Input<'_>, randomness: &[u8]) -> Output<S::Hash> {
+    S::Hash::new()
+        .chain_update((context.as_ref().len() as u16).to_be_bytes())
+        .chain_update(context)
+        .chain_update((randomness.len() as u16).to_be_bytes())
+        .chain_update(randomness)
+        .chain_update("PossessionNonce")
+        .finalize()
+}
+
+/// The challenge of a proof of possession.
+///
+/// Computed as `HashToScalar(I2OSP(len(pkS), 2) || pkS || I2OSP(len(t), 2) || t ||
+/// I2OSP(len(context), 2) || context || "Possession")`.
+fn challenge<S: Suite, M: Mode>(
+    verifying_key: S::Group,
+    t: S::Group,
+    context: Input<'_>,
+) -> <S::Group as Group>::Scalar {
+    let mut elements = [Default::default(); 2];
+    S::batch_to_bytes(&[verifying_key, t], &mut elements);
+    let [pk, t]: [<S::Group as GroupEncoding>::Repr; 2] = elements;
+
+    let transcript = [
+        &(pk.as_ref().len() as u16).to_be_bytes(),
+        pk.as_ref(),
+        &(t.as_ref().len() as u16).to_be_bytes(),
+        t.as_ref(),
+        &(context.as_ref().len() as u16).to_be_bytes(),
+        context.as_ref(),
+        b"Possession",
+    ];
+    hash_to_scalar::<S, M>(&transcript)
+}
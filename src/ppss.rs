@@ -0,0 +1,355 @@
+//! Password-protected secret sharing (PPSS) over multiple OPRF servers.
+//!
+//! A secret is split with Shamir's secret sharing into one share per server, and each share is
+//! encrypted under the output of the OPRF of that server over a short PIN. The [`Backup`] of the
+//! encrypted shares can be stored anywhere: recovering the secret requires the PIN *and* the
+//! cooperation of `threshold` servers, each of which can rate limit guesses for the backup
+//! `identifier` used as the shared `info` of the [`Partial`] mode.
+//!
+//! Both creating and recovering a backup start with a [`PinRequest`] to the servers. Servers are
+//! identified by their index in the list of [`VerifyingKey`]s provided to [`PinRequest::new`],
+//! which must be the same list when creating and recovering a backup.
+
+use alloc::vec::Vec;
+
+use digest::{Digest, Output};
+use group::{
+    Group,
+    ff::{Field, PrimeField},
+};
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    expand,
+    mode::Partial,
+    server::SecretKey,
+};
+
+type Scalar<S> = <<S as Suite>::Group as Group>::Scalar;
+
+/// Request of the PIN outputs to the servers.
+pub struct PinRequest<'a, S: Suite> {
+    clients: Vec<Option<Client<'a, 'a, 1, S, Partial>>>,
+}
+
+impl<'a, S: Suite> PinRequest<'a, S> {
+    /// Blind the PIN for every server.
+    ///
+    /// Returns the request, and the blinded element to send to each server alongside the
+    /// `identifier` of the backup. Only the servers that are actually queried need to receive their
+    /// blinded element.
+    #[allow(clippy::type_complexity)]
+    pub fn new(
+        pin: Input<'a>,
+        identifier: Input<'a>,
+        verifying_keys: &[VerifyingKey<S::Group>],
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, Vec<Blinded<S::Group>>), InvalidInput> {
+        let mut clients = Vec::with_capacity(verifying_keys.len());
+        let mut blinded_elements = Vec::with_capacity(verifying_keys.len());
+        for verifying_key in verifying_keys {
+            let (client, [blinded_element]) =
+                Client::<_, S, Partial>::blind([pin], identifier, *verifying_key, rng)?;
+            clients.push(Some(client));
+            blinded_elements.push(blinded_element);
+        }
+
+        Ok((PinRequest { clients }, blinded_elements))
+    }
+
+    /// Verify the responses of the servers, and compute the PIN outputs.
+    ///
+    /// Each response is tagged with the index of the server that produced it. Responses from
+    /// unknown or repeated servers are rejected as invalid.
+    #[allow(clippy::type_complexity)]
+    pub fn finish(
+        mut self,
+        responses: &[(usize, Evaluated<S::Group>, Proof<Scalar<S>>)],
+    ) -> Result<Vec<PinOutput<S>>, InvalidProof> {
+        responses
+            .iter()
+            .map(|(index, evaluated_element, proof)| {
+                let client = self
+                    .clients
+                    .get_mut(*index)
+                    .and_then(Option::take)
+                    .ok_or(InvalidProof)?;
+                let [output] = client.finalize([*evaluated_element], *proof)?;
+                Ok(PinOutput {
+                    index: *index,
+                    output,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The output of the OPRF of a server over the PIN.
+pub struct PinOutput<S: Suite> {
+    /// The index of the server.
+    pub index: usize,
+    /// The output of the OPRF.
+    pub output: Output<S::Hash>,
+}
+
+/// A share of the secret, encrypted under the PIN output of a server.
+pub struct EncryptedShare<S: Suite> {
+    /// The index of the server.
+    pub index: usize,
+    /// The encrypted share.
+    pub ciphertext: <Scalar<S> as PrimeField>::Repr,
+}
+
+impl<S: Suite> Clone for EncryptedShare<S> {
+    fn clone(&self) -> Self {
+        EncryptedShare {
+            index: self.index,
+            ciphertext: self.ciphertext,
+        }
+    }
+}
+
+impl<S: Suite> core::fmt::Debug for EncryptedShare<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EncryptedShare")
+            .field("index", &self.index)
+            .field("ciphertext", &self.ciphertext.as_ref())
+            .finish()
+    }
+}
+
+/// A backup of a secret, protected by a PIN.
+pub struct Backup<S: Suite> {
+    threshold: usize,
+    shares: Vec<EncryptedShare<S>>,
+    commitment: Output<S::Hash>,
+}
+
+impl<S: Suite> Backup<S> {
+    /// Create a backup of a new random secret.
+    ///
+    /// The `outputs` must contain the [`PinOutput`] of every server. Any `threshold` of them will be
+    /// needed to recover the secret.
+    ///
+    /// Returns the backup, and the key derived from the secret.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is zero or larger than the number of `outputs`.
+    pub fn create<const K: usize>(
+        threshold: usize,
+        outputs: &[PinOutput<S>],
+        rng: &mut impl RngCore,
+    ) -> (Self, BackupKey<K>) {
+        assert!(
+            threshold > 0 && threshold <= outputs.len(),
+            "invalid threshold"
+        );
+
+        let coefficients: Vec<SecretKey<Scalar<S>>> = (0..threshold)
+            .map(|_| SecretKey::new(Scalar::<S>::random(&mut *rng)))
+            .collect();
+        let shares = outputs
+            .iter()
+            .map(|PinOutput { index, output }| {
+                let x = share_point::<S>(*index);
+                let share = SecretKey::new(
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(Scalar::<S>::ZERO, |acc, coefficient| {
+                            acc * x + coefficient.expose_secret()
+                        }),
+                );
+                EncryptedShare {
+                    index: *index,
+                    ciphertext: mask::<S>(share.expose_secret().to_repr(), output),
+                }
+            })
+            .collect();
+
+        let secret = &coefficients[0];
+        (
+            Backup {
+                threshold,
+                shares,
+                commitment: commitment::<S>(secret),
+            },
+            BackupKey::from_secret::<S>(secret),
+        )
+    }
+
+    /// Restore a backup from its parts.
+    pub fn from_parts(
+        threshold: usize,
+        shares: Vec<EncryptedShare<S>>,
+        commitment: Output<S::Hash>,
+    ) -> Self {
+        Backup {
+            threshold,
+            shares,
+            commitment,
+        }
+    }
+
+    /// The number of servers needed to recover the secret.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The encrypted shares of the secret.
+    pub fn shares(&self) -> &[EncryptedShare<S>] {
+        &self.shares
+    }
+
+    /// The commitment to the secret, used to detect a wrong PIN.
+    pub fn commitment(&self) -> &Output<S::Hash> {
+        &self.commitment
+    }
+
+    /// Recover the key from the PIN outputs of at least `threshold` servers.
+    ///
+    /// Fails with [`RecoveryError::DuplicateShare`] if two outputs have the same index.
+    pub fn recover<const K: usize>(
+        &self,
+        outputs: &[PinOutput<S>],
+    ) -> Result<BackupKey<K>, RecoveryError> {
+        if outputs
+            .iter()
+            .enumerate()
+            .any(|(i, output)| outputs[..i].iter().any(|other| other.index == output.index))
+        {
+            return Err(RecoveryError::DuplicateShare);
+        }
+
+        let shares = outputs
+            .iter()
+            .filter_map(|PinOutput { index, output }| {
+                let share = self.shares.iter().find(|share| share.index == *index)?;
+                let mut repr = mask::<S>(share.ciphertext, output);
+                let share = Scalar::<S>::from_repr(repr).map(SecretKey::new);
+                zeroize::Zeroize::zeroize(repr.as_mut());
+                Some((share_point::<S>(*index), share.into_option()))
+            })
+            .take(self.threshold)
+            .collect::<Vec<_>>();
+        if shares.len() < self.threshold {
+            return Err(RecoveryError::NotEnoughShares);
+        }
+        // With a wrong PIN, decrypted shares may not even be valid scalars.
+        let shares = shares
+            .into_iter()
+            .map(|(x, y)| Some((x, y?)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(RecoveryError::WrongPin)?;
+
+        let secret = SecretKey::new(shares.iter().fold(Scalar::<S>::ZERO, |acc, (x_i, y_i)| {
+            let (numerator, denominator) = shares
+                .iter()
+                .filter(|(x_j, _)| x_j != x_i)
+                .fold((Scalar::<S>::ONE, Scalar::<S>::ONE), |(n, d), (x_j, _)| {
+                    (n * x_j, d * (*x_j - x_i))
+                });
+            acc + *y_i.expose_secret()
+                * numerator
+                * denominator.invert().expect("share points are distinct")
+        }));
+
+        if commitment::<S>(&secret) != self.commitment {
+            return Err(RecoveryError::WrongPin);
+        }
+        Ok(BackupKey::from_secret::<S>(&secret))
+    }
+}
+
+/// The `x` coordinate of the share of the server at `index`.
+fn share_point<S: Suite>(index: usize) -> Scalar<S> {
+    Scalar::<S>::from(index as u64 + 1)
+}
+
+/// Encrypt or decrypt a share with the PIN output of its server.
+fn mask<S: Suite>(
+    mut share: <Scalar<S> as PrimeField>::Repr,
+    output: &Output<S::Hash>,
+) -> <Scalar<S> as PrimeField>::Repr {
+    let mut pad = <Scalar<S> as PrimeField>::Repr::default();
+    expand::<S>(output, b"Share", pad.as_mut());
+    share
+        .as_mut()
+        .iter_mut()
+        .zip(pad.as_ref())
+        .for_each(|(s, p)| *s ^= p);
+    share
+}
+
+/// Commitment to the secret.
+fn commitment<S: Suite>(secret: &SecretKey<Scalar<S>>) -> Output<S::Hash> {
+    let mut repr = secret.expose_secret().to_repr();
+    let commitment = S::Hash::new()
+        .chain_update(repr.as_ref())
+        .chain_update("Commitment")
+        .finalize();
+    zeroize::Zeroize::zeroize(repr.as_mut());
+    commitment
+}
+
+/// The key protected by a [`Backup`].
+///
+/// The key is zeroized on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BackupKey<const K: usize>(pub [u8; K]);
+
+impl<const K: usize> BackupKey<K> {
+    fn from_secret<S: Suite>(secret: &SecretKey<Scalar<S>>) -> Self {
+        let mut repr = secret.expose_secret().to_repr();
+        let mut key = [0; K];
+        expand::<S>(repr.as_ref(), b"BackupKey", &mut key);
+        zeroize::Zeroize::zeroize(repr.as_mut());
+        BackupKey(key)
+    }
+}
+
+impl<const K: usize> zeroize::Zeroize for BackupKey<K> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const K: usize> Drop for BackupKey<K> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl<const K: usize> zeroize::ZeroizeOnDrop for BackupKey<K> {}
+
+impl<const K: usize> core::fmt::Debug for BackupKey<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("BackupKey(..)")
+    }
+}
+
+/// Error recovering a [`Backup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RecoveryError {
+    /// Less than `threshold` servers provided an output for a share of the backup.
+    NotEnoughShares,
+    /// Several outputs were provided for the same server.
+    DuplicateShare,
+    /// The recovered secret does not match the backup, the PIN is likely wrong.
+    WrongPin,
+}
+
+impl core::fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RecoveryError::NotEnoughShares => write!(f, "not enough shares to recover the backup"),
+            RecoveryError::DuplicateShare => write!(f, "several outputs are for the same share"),
+            RecoveryError::WrongPin => write!(f, "the recovered secret does not match the backup"),
+        }
+    }
+}
+
+impl core::error::Error for RecoveryError {}
@@ -0,0 +1,111 @@
+//! Precomputation for long-lived clients verifying many proofs from the same server.
+//!
+//! A [`PreparedVerifyingKey`] holds tables of multiples of the generator and of the verifying key
+//! of a server, and the seed of the composites of its proofs in the [`Verifiable`] mode. Built
+//! once, it is passed to:
+//! - [`Client<_, Verifiable>::finalize_prepared`], which verifies proofs without multiplying the
+//!   generator or the verifying key from scratch.
+//! - [`Client<_, Partial>::blind_prepared`] and [`Client<_, Partial>::finalize_prepared`], which
+//!   also compute the tweaked key of the server from the tables.
+//!
+//! The tables take a few dozen kilobytes per key. Multiplications with them do not run in constant
+//! time, which is fine as they are only used with public scalars.
+//!
+//! [`Client<_, Verifiable>::finalize_prepared`]: crate::client::Client#method.finalize_prepared
+//! [`Client<_, Partial>::blind_prepared`]: crate::client::Client#method.blind_prepared
+//! [`Client<_, Partial>::finalize_prepared`]: crate::client::Client#method.finalize_prepared-1
+
+use core::marker::PhantomData;
+
+use digest::Output;
+use group::Group;
+
+use crate::{
+    Suite, VerifyingKey, composite_seed,
+    mode::{Mode, Partial, Verifiable},
+    msm::FixedBase,
+};
+
+/// A [`VerifyingKey`] with precomputed tables, for the mode `M`.
+pub struct PreparedVerifyingKey<S: Suite, M> {
+    verifying_key: VerifyingKey<S::Group>,
+    generator_table: FixedBase<S::Group>,
+    key_table: FixedBase<S::Group>,
+    /// Only in the `Verifiable` mode, as the seed depends on `info` in the `Partial` mode.
+    seed: Option<Output<S::Hash>>,
+    mode: PhantomData<M>,
+}
+
+impl<S: Suite, M: Mode> PreparedVerifyingKey<S, M> {
+    /// Precompute the tables for `verifying_key`.
+    pub fn new(verifying_key: VerifyingKey<S::Group>) -> Self {
+        PreparedVerifyingKey {
+            verifying_key,
+            generator_table: FixedBase::new(S::Group::generator()),
+            key_table: FixedBase::new(verifying_key.0),
+            seed: (M::IDENTIFIER.get() == Verifiable::IDENTIFIER.get())
+                .then(|| composite_seed::<S, M>(verifying_key.0)),
+            mode: PhantomData,
+        }
+    }
+
+    /// The verifying key the tables were computed for.
+    pub fn verifying_key(&self) -> VerifyingKey<S::Group> {
+        self.verifying_key
+    }
+}
+
+impl<S: Suite> PreparedVerifyingKey<S, Verifiable> {
+    /// The seed of the composites of the proofs.
+    pub(crate) fn seed(&self) -> &Output<S::Hash> {
+        self.seed
+            .as_ref()
+            .expect("the seed is computed in the verifiable mode")
+    }
+
+    /// Compute `generator * s + verifying_key * c`.
+    pub(crate) fn t2(
+        &self,
+        s: <S::Group as Group>::Scalar,
+        c: <S::Group as Group>::Scalar,
+    ) -> S::Group {
+        self.generator_table.mul_vartime(&s) + self.key_table.mul_vartime(&c)
+    }
+}
+
+impl<S: Suite> PreparedVerifyingKey<S, Partial> {
+    /// Compute the tweaked key `generator * m + verifying_key` for the scalar `m` of the `info`.
+    pub(crate) fn tweaked_key(&self, m: &<S::Group as Group>::Scalar) -> S::Group {
+        self.generator_table.mul_vartime(m) + self.verifying_key.0
+    }
+
+    /// Compute `generator * s + tweaked_key * c` for the scalar `m` of the `info`.
+    pub(crate) fn t2(
+        &self,
+        m: <S::Group as Group>::Scalar,
+        s: <S::Group as Group>::Scalar,
+        c: <S::Group as Group>::Scalar,
+    ) -> S::Group {
+        self.generator_table.mul_vartime(&(s + c * m)) + self.key_table.mul_vartime(&c)
+    }
+}
+
+impl<S: Suite, M> Clone for PreparedVerifyingKey<S, M> {
+    fn clone(&self) -> Self {
+        PreparedVerifyingKey {
+            verifying_key: self.verifying_key,
+            generator_table: self.generator_table.clone(),
+            key_table: self.key_table.clone(),
+            seed: self.seed.clone(),
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<S: Suite, M> core::fmt::Debug for PreparedVerifyingKey<S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PreparedVerifyingKey")
+            .field("verifying_key", &self.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
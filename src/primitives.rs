@@ -0,0 +1,100 @@
+//! The building blocks of the protocol, parametrized by the mode.
+//!
+//! The protocol methods of [`Client`](crate::client::Client) and [`Server`] only run the modes of
+//! RFC 9497. A custom [`Mode`] runs its own protocol with these functions, which separate their
+//! domains with the identifier of the mode like those of RFC 9497:
+//! - [`hash_to_group`] and [`hash_to_scalar`] hash with the context string of the suite and mode.
+//! - [`prove`] and [`verify`] produce and check the DLEQ proof of RFC 9497 that elements were
+//!   multiplied by the secret key of a server.
+//! - [`finalize`] hashes an input and its unblinded element into the output.
+
+use digest::Output;
+use group::Group;
+use rand_core::RngCore;
+
+use crate::{
+    Input, Proof, Suite, VerifyingKey,
+    client::{InvalidInput, InvalidProof},
+    composite_seed, derive_nonce, finalize_hash, generate_proof_with_seed,
+    mode::Mode,
+    server::Server,
+    verify_proof,
+};
+
+/// Hash the `input` to the group, with the domain of the suite `S` and the mode `M`.
+///
+/// Fails with [`InvalidInput`] if the `input` hashes to the identity.
+pub fn hash_to_group<S: Suite, M: Mode>(input: Input<'_>) -> Result<S::Group, InvalidInput> {
+    let element = crate::hash_to_group::<S, M>(&[input.as_ref()]);
+    if element.is_identity().into() {
+        return Err(InvalidInput);
+    }
+
+    Ok(element)
+}
+
+/// Hash the concatenation of the `input` slices to a scalar, with the domain of the suite `S` and
+/// the mode `M`.
+pub fn hash_to_scalar<S: Suite, M: Mode>(input: &[&[u8]]) -> <S::Group as Group>::Scalar {
+    crate::hash_to_scalar::<S, M>(input)
+}
+
+/// Prove that each element of `d` is the element of `c` at the same index multiplied by the secret
+/// key of the `server`.
+///
+/// The nonce is hedged as in [`Server::evaluate_hedged`](Server#method.evaluate_hedged), with
+/// randomness drawn from `rng`. The proof is checked with [`verify`].
+///
+/// # Panics
+///
+/// Panics if `c` and `d` have different lengths, or more than `2^16` elements.
+pub fn prove<S: Suite, M: Mode>(
+    server: &Server<S, M>,
+    c: &[S::Group],
+    d: &[S::Group],
+    rng: &mut impl RngCore,
+) -> Proof<<S::Group as Group>::Scalar> {
+    assert_eq!(c.len(), d.len(), "c and d must have the same length");
+    let k = *server.secret_key().expose_secret();
+    let b = server.verifying_key().0;
+    let mut randomness = [0; 32];
+    rng.fill_bytes(&mut randomness);
+
+    generate_proof_with_seed::<S, M>(
+        k,
+        S::Group::generator(),
+        b,
+        &composite_seed::<S, M>(b),
+        c,
+        d,
+        |m, z| derive_nonce::<S, M>(k, m, z, &randomness),
+    )
+    .0
+}
+
+/// Verify a proof produced by [`prove`] for the `verifying_key` of the server, and the elements `c`
+/// and `d`.
+pub fn verify<S: Suite, M: Mode>(
+    verifying_key: VerifyingKey<S::Group>,
+    c: &[S::Group],
+    d: &[S::Group],
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> Result<(), InvalidProof> {
+    if c.len() != d.len()
+        || !verify_proof::<S, M>(S::Group::generator(), verifying_key.0, c, d, proof)
+    {
+        return Err(InvalidProof);
+    }
+
+    Ok(())
+}
+
+/// Hash the `input`, the optional public `info` and the `unblinded_element` into the output, as in
+/// `Finalize` of RFC 9497.
+pub fn finalize<S: Suite>(
+    input: Input<'_>,
+    info: Option<Input<'_>>,
+    unblinded_element: S::Group,
+) -> Output<S::Hash> {
+    finalize_hash::<S>(input, info, unblinded_element)
+}
@@ -0,0 +1,249 @@
+//! Deterministic pseudonymization of identifiers.
+//!
+//! A data owner turns identifiers (emails, phone numbers, ...) into stable pseudonyms with the help
+//! of a key holder running a [`Server`] in the [`Partial`] mode. The key holder never sees the raw
+//! identifiers, and the data owner never learns the key, so pseudonyms cannot be computed by either
+//! party alone.
+//!
+//! The `context` of a [`Pseudonymizer`] is used as the shared `info` of the POPRF, so pseudonyms
+//! computed in different contexts (e.g. for different datasets or analytics partners) are
+//! unlinkable.
+//!
+//! Identifiers are processed in chunks of `C` elements, each chunk producing a single [`Proof`].
+//! The last chunk is padded with repeated identifiers if needed.
+
+use alloc::{string::String, vec::Vec};
+
+use group::Group;
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    mode::Partial,
+    server::{Server, UndefinedInverse},
+};
+
+/// The textual encoding of a pseudonym.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Encoding {
+    /// Lowercase hexadecimal.
+    #[default]
+    Hex,
+    /// Lowercase base32 without padding, as defined in RFC 4648.
+    Base32,
+    /// URL safe base64 without padding, as defined in RFC 4648.
+    Base64Url,
+}
+
+impl Encoding {
+    /// Encode the provided bytes.
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Encoding::Hex => encode_bits(bytes, 4, b"0123456789abcdef"),
+            Encoding::Base32 => encode_bits(bytes, 5, b"abcdefghijklmnopqrstuvwxyz234567"),
+            Encoding::Base64Url => encode_bits(
+                bytes,
+                6,
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+            ),
+        }
+    }
+}
+
+/// Encode `bytes` in groups of `bits` bits using the provided alphabet, without padding.
+fn encode_bits(bytes: &[u8], bits: u32, alphabet: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(bits as usize));
+    let mut buffer = 0u32;
+    let mut buffered = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        buffered += 8;
+        while buffered >= bits {
+            buffered -= bits;
+            encoded.push(alphabet[((buffer >> buffered) & ((1 << bits) - 1)) as usize] as char);
+        }
+    }
+    if buffered > 0 {
+        encoded
+            .push(alphabet[((buffer << (bits - buffered)) & ((1 << bits) - 1)) as usize] as char);
+    }
+    encoded
+}
+
+/// Data owner side of the pseudonymization.
+///
+/// Holds the [`VerifyingKey`] of the key holder, the context in which pseudonyms are computed, and
+/// the output format of the pseudonyms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pseudonymizer<'b, E> {
+    verifying_key: VerifyingKey<E>,
+    context: Input<'b>,
+    encoding: Encoding,
+    length: Option<usize>,
+}
+
+impl<'b, E> Pseudonymizer<'b, E> {
+    /// Create a pseudonymizer for the provided context, encoding pseudonyms in [`Encoding::Hex`].
+    pub fn new(verifying_key: VerifyingKey<E>, context: Input<'b>) -> Self {
+        Pseudonymizer {
+            verifying_key,
+            context,
+            encoding: Encoding::default(),
+            length: None,
+        }
+    }
+
+    /// Set the encoding of the pseudonyms.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Truncate the PRF outputs to `length` bytes before encoding them.
+    ///
+    /// Shorter pseudonyms are more likely to collide. Lengths larger than the output size of the
+    /// suite's hash function have no effect.
+    pub fn with_length(mut self, length: usize) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// The context in which pseudonyms are computed.
+    pub fn context(&self) -> Input<'b> {
+        self.context
+    }
+
+    /// Blind a batch of identifiers.
+    ///
+    /// Returns the pending [`Batch`], and the chunks of blinded elements to send to the key holder.
+    #[allow(clippy::type_complexity)]
+    pub fn blind<'a, S: Suite<Group = E>, const C: usize>(
+        &self,
+        identifiers: &[Input<'a>],
+        rng: &mut impl RngCore,
+    ) -> Result<(Batch<'a, 'b, C, S>, Vec<[Blinded<E>; C]>), InvalidInput>
+    where
+        E: Group,
+    {
+        const { assert!(C > 0, "chunks must not be empty") };
+
+        let mut clients = Vec::with_capacity(identifiers.len().div_ceil(C));
+        let mut blinded_elements = Vec::with_capacity(clients.capacity());
+        for chunk in identifiers.chunks(C) {
+            let inputs = core::array::from_fn(|i| chunk.get(i).copied().unwrap_or(chunk[0]));
+            let (client, blinded) =
+                Client::<_, S, Partial>::blind(inputs, self.context, self.verifying_key, rng)?;
+            clients.push(client);
+            blinded_elements.push(blinded);
+        }
+
+        Ok((
+            Batch {
+                clients,
+                len: identifiers.len(),
+                encoding: self.encoding,
+                length: self.length,
+            },
+            blinded_elements,
+        ))
+    }
+}
+
+/// A batch of identifiers waiting for evaluation by the key holder.
+pub struct Batch<'a, 'b, const C: usize, S: Suite> {
+    clients: Vec<Client<'a, 'b, C, S, Partial>>,
+    len: usize,
+    encoding: Encoding,
+    length: Option<usize>,
+}
+
+impl<const C: usize, S: Suite> Batch<'_, '_, C, S> {
+    /// The number of identifiers in the batch.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the batch contains no identifiers.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Verify the evaluations of the key holder and compute the pseudonyms.
+    ///
+    /// The pseudonyms are returned in the same order as the identifiers provided to
+    /// [`Pseudonymizer::blind`]. `evaluations` must hold one evaluation per chunk of the batch.
+    #[allow(clippy::type_complexity)]
+    pub fn finalize(
+        self,
+        evaluations: &[([Evaluated<S::Group>; C], Proof<<S::Group as Group>::Scalar>)],
+    ) -> Result<Vec<String>, FinalizeError> {
+        if evaluations.len() != self.clients.len() {
+            return Err(FinalizeError::LengthMismatch);
+        }
+
+        let mut pseudonyms = Vec::with_capacity(self.len);
+        for (client, (evaluated_elements, proof)) in self.clients.into_iter().zip(evaluations) {
+            let outputs = client.finalize(*evaluated_elements, *proof)?;
+            pseudonyms.extend(outputs.iter().map(|output| {
+                let length = self.length.unwrap_or(output.len()).min(output.len());
+                self.encoding.encode(&output[..length])
+            }));
+        }
+        pseudonyms.truncate(self.len);
+
+        Ok(pseudonyms)
+    }
+}
+
+/// Key holder side of the pseudonymization.
+///
+/// Evaluates the chunks of blinded elements produced by [`Pseudonymizer::blind`] in the provided
+/// context.
+#[allow(clippy::type_complexity)]
+pub fn evaluate<S: Suite, const C: usize>(
+    server: &Server<S, Partial>,
+    blinded_elements: &[[Blinded<S::Group>; C]],
+    context: Input<'_>,
+    rng: &mut impl RngCore,
+) -> Result<Vec<([Evaluated<S::Group>; C], Proof<<S::Group as Group>::Scalar>)>, UndefinedInverse> {
+    blinded_elements
+        .iter()
+        .map(|blinded| server.evaluate(*blinded, context, rng))
+        .collect()
+}
+
+/// Error returned by [`Batch::finalize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FinalizeError {
+    /// The number of evaluations is not the number of chunks of the batch.
+    LengthMismatch,
+    /// The proof of one of the evaluations is invalid.
+    InvalidProof(InvalidProof),
+}
+
+impl From<InvalidProof> for FinalizeError {
+    fn from(error: InvalidProof) -> Self {
+        FinalizeError::InvalidProof(error)
+    }
+}
+
+impl core::fmt::Display for FinalizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FinalizeError::LengthMismatch => {
+                write!(f, "the number of evaluations does not match the batch")
+            }
+            FinalizeError::InvalidProof(e) => e.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for FinalizeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            FinalizeError::LengthMismatch => None,
+            FinalizeError::InvalidProof(e) => Some(e),
+        }
+    }
+}
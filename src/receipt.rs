@@ -0,0 +1,275 @@
+//! Exportable evaluation receipts, for third-party audit of a server.
+//!
+//! A client that received [`Evaluated`] elements and a [`Proof`] from a server in the
+//! [`Verifiable`] or [`Partial`] mode can record them in a [`Receipt`]. The receipt contains
+//! everything needed to re-run the verification of the proof, but not the inputs of the client nor
+//! its blinds, so it can be handed to an auditor to keep verifiable logs of the behavior of the
+//! server.
+//!
+//! A receipt may also record the [`Commitments`] of the proof, when the server sent them, so that
+//! it can be verified along with many others by [`batch::verify`](crate::batch::verify).
+//!
+//! # Encoding
+//!
+//! [`Receipt::to_bytes`] produces a stable encoding, where every variable length field is prefixed
+//! by its length as a big-endian `u16`:
+//!
+//! ```text
+//! "OPRFReceipt" || version (1 byte) || suite identifier || mode identifier (1 byte)
+//!     || verifying key || I2OSP(N, 2) || N blinded elements || N evaluated elements
+//!     || info (Partial mode only) || proof challenge || proof response
+//!     || M || Z || t2 || t3 (only with commitments)
+//! ```
+
+use alloc::vec::Vec;
+
+use digest::Output;
+use group::{Group, GroupEncoding, ff::PrimeField};
+
+use crate::{
+    Blinded, Commitments, Evaluated, InvalidEncoding, Proof, Suite, VerifyingKey, challenge,
+    client::InvalidProof,
+    composite_seed,
+    encoding::{Reader, put},
+    info_scalar,
+    mode::{Mode, Partial, Verifiable},
+    verify_proof_with_seed,
+};
+
+const MAGIC: &[u8] = b"OPRFReceipt";
+const VERSION: u8 = 1;
+
+/// Record of an evaluation by the server, and of its proof.
+///
+/// The receipt is for the [`Partial`] mode when `info` is present, and for the [`Verifiable`] mode
+/// otherwise.
+pub struct Receipt<S: Suite> {
+    /// The verifying key the client pinned for the server.
+    pub verifying_key: VerifyingKey<S::Group>,
+    /// The blinded elements sent to the server.
+    pub blinded_elements: Vec<Blinded<S::Group>>,
+    /// The evaluated elements returned by the server.
+    pub evaluated_elements: Vec<Evaluated<S::Group>>,
+    /// The shared info, in the [`Partial`] mode.
+    pub info: Option<Vec<u8>>,
+    /// The proof returned by the server.
+    pub proof: Proof<<S::Group as Group>::Scalar>,
+    /// The commitments of the proof, if the server sent them.
+    pub commitments: Option<Commitments<S::Group>>,
+}
+
+impl<S: Suite> Clone for Receipt<S> {
+    fn clone(&self) -> Self {
+        Receipt {
+            verifying_key: self.verifying_key,
+            blinded_elements: self.blinded_elements.clone(),
+            evaluated_elements: self.evaluated_elements.clone(),
+            info: self.info.clone(),
+            proof: self.proof,
+            commitments: self.commitments,
+        }
+    }
+}
+
+impl<S: Suite> core::fmt::Debug for Receipt<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Receipt")
+            .field("verifying_key", &self.verifying_key)
+            .field("blinded_elements", &self.blinded_elements)
+            .field("evaluated_elements", &self.evaluated_elements)
+            .field("info", &self.info)
+            .field("proof", &self.proof)
+            .field("commitments", &self.commitments)
+            .finish()
+    }
+}
+
+impl<S: Suite> PartialEq for Receipt<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.verifying_key == other.verifying_key
+            && self.blinded_elements == other.blinded_elements
+            && self.evaluated_elements == other.evaluated_elements
+            && self.info == other.info
+            && self.proof == other.proof
+            && self.commitments == other.commitments
+    }
+}
+
+impl<S: Suite> Eq for Receipt<S> {}
+
+impl<S: Suite> Receipt<S> {
+    /// Verify the proof of the receipt.
+    ///
+    /// This is the check performed by the [`Client`](crate::client::Client) when finalizing, and
+    /// the check that the commitments, if any, are those of the proof. To verify many receipts at
+    /// once, see [`batch::verify`](crate::batch::verify).
+    pub fn verify(&self) -> Result<(), InvalidProof> {
+        let key = self.key()?;
+        self.verify_with_seed(key, &self.seed(key))
+    }
+
+    /// The key proven by the server: its verifying key, tweaked by `info` in the [`Partial`] mode.
+    pub(crate) fn key(&self) -> Result<S::Group, InvalidProof> {
+        let Some(info) = &self.info else {
+            return Ok(self.verifying_key.0);
+        };
+        let info = info.as_slice().try_into().map_err(|_| InvalidProof)?;
+        let tweaked_key =
+            S::Group::mul_by_generator(&info_scalar::<S>(info)) + self.verifying_key.0;
+        if tweaked_key.is_identity().into() {
+            return Err(InvalidProof);
+        }
+        Ok(tweaked_key)
+    }
+
+    /// The seed of the composites for the `key` returned by [`Receipt::key`].
+    pub(crate) fn seed(&self, key: S::Group) -> Output<S::Hash> {
+        match self.info {
+            None => composite_seed::<S, Verifiable>(key),
+            Some(_) => composite_seed::<S, Partial>(key),
+        }
+    }
+
+    /// Verify the proof with the `key` and `seed` returned by [`Receipt::key`] and
+    /// [`Receipt::seed`].
+    pub(crate) fn verify_with_seed(
+        &self,
+        key: S::Group,
+        seed: &Output<S::Hash>,
+    ) -> Result<(), InvalidProof> {
+        if self.blinded_elements.len() != self.evaluated_elements.len()
+            || self.blinded_elements.len() > u16::MAX as usize
+        {
+            return Err(InvalidProof);
+        }
+        let blinded_elements: Vec<_> = self.blinded_elements.iter().map(|b| b.0).collect();
+        let evaluated_elements: Vec<_> = self.evaluated_elements.iter().map(|e| e.0).collect();
+
+        let valid = match self.info {
+            None => verify_proof_with_seed::<S, Verifiable>(
+                S::Group::generator(),
+                key,
+                seed,
+                &blinded_elements,
+                &evaluated_elements,
+                self.proof,
+            ),
+            Some(_) => verify_proof_with_seed::<S, Partial>(
+                S::Group::generator(),
+                key,
+                seed,
+                &evaluated_elements,
+                &blinded_elements,
+                self.proof,
+            ),
+        };
+        if !valid {
+            return Err(InvalidProof);
+        }
+
+        // The proof is valid, so the commitments hashing to its challenge are its own.
+        if let Some(Commitments { m, z, t2, t3 }) = self.commitments {
+            let c = match self.info {
+                None => challenge::<S, Verifiable>(key, m, z, t2, t3),
+                Some(_) => challenge::<S, Partial>(key, m, z, t2, t3),
+            };
+            if c != self.proof.c {
+                return Err(InvalidProof);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encode the receipt.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the receipt contains more than `u16::MAX` elements, or `info` longer than
+    /// `u16::MAX` bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        put(&mut out, S::IDENTIFIER);
+        out.push(match self.info {
+            None => Verifiable::IDENTIFIER.get(),
+            Some(_) => Partial::IDENTIFIER.get(),
+        });
+        put(&mut out, self.verifying_key.0.to_bytes().as_ref());
+
+        let len = u16::try_from(self.blinded_elements.len()).expect("less than 2^16 elements");
+        out.extend_from_slice(&len.to_be_bytes());
+        for Blinded(element) in &self.blinded_elements {
+            put(&mut out, element.to_bytes().as_ref());
+        }
+        for Evaluated(element) in &self.evaluated_elements {
+            put(&mut out, element.to_bytes().as_ref());
+        }
+
+        if let Some(info) = &self.info {
+            put(&mut out, info);
+        }
+        put(&mut out, self.proof.c.to_repr().as_ref());
+        put(&mut out, self.proof.s.to_repr().as_ref());
+        if let Some(Commitments { m, z, t2, t3 }) = self.commitments {
+            for element in [m, z, t2, t3] {
+                put(&mut out, element.to_bytes().as_ref());
+            }
+        }
+        out
+    }
+
+    /// Decode a receipt produced by [`Receipt::to_bytes`] for the same suite.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InvalidEncoding> {
+        let mut reader = Reader::new(bytes);
+        if reader.bytes(MAGIC.len())? != MAGIC
+            || reader.u8()? != VERSION
+            || reader.field()? != S::IDENTIFIER
+        {
+            return Err(InvalidEncoding);
+        }
+        let partial = match reader.u8()? {
+            mode if mode == Verifiable::IDENTIFIER.get() => false,
+            mode if mode == Partial::IDENTIFIER.get() => true,
+            _ => return Err(InvalidEncoding),
+        };
+        let verifying_key = VerifyingKey(reader.element()?);
+
+        let len = reader.u16()? as usize;
+        let blinded_elements = (0..len)
+            .map(|_| reader.element().map(Blinded))
+            .collect::<Result<_, _>>()?;
+        let evaluated_elements = (0..len)
+            .map(|_| reader.element().map(Evaluated))
+            .collect::<Result<_, _>>()?;
+
+        let info = match partial {
+            true => Some(reader.field()?.to_vec()),
+            false => None,
+        };
+        let proof = Proof {
+            c: reader.scalar()?,
+            s: reader.scalar()?,
+        };
+        let commitments = match reader.remaining() {
+            0 => None,
+            _ => Some(Commitments {
+                m: reader.element()?,
+                z: reader.element()?,
+                t2: reader.element()?,
+                t3: reader.element()?,
+            }),
+        };
+        reader.finish()?;
+
+        Ok(Receipt {
+            verifying_key,
+            blinded_elements,
+            evaluated_elements,
+            info,
+            proof,
+            commitments,
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//! Scheduled key rotation, with key identifiers and grace periods.
+//!
+//! A [`KeyManager`] holds the successive keys of a server. Each key is identified by the
+//! [`KeyId`] of its [`VerifyingKey`], and is valid in a window of time `[activation, expiry)`,
+//! where times are `u64` in any unit the application chooses, e.g. UNIX seconds or epoch numbers.
+//!
+//! - New evaluations use the [current](KeyManager::current) key: the most recently activated key
+//!   that has not expired. Clients include its [`KeyId`] in their requests, and the server routes
+//!   them with [`KeyManager::evaluation_key`], which rejects keys outside of their window.
+//! - Redemptions, i.e. recomputations of outputs with
+//!   [`Server::full_evaluate`](Server#method.full_evaluate), are routed with
+//!   [`KeyManager::redemption_key`], which still accepts a key for the grace period of the manager
+//!   after it expired. Outputs produced just before a rotation can then still be redeemed.
+//!
+//! Keys past their grace period are removed by [`KeyManager::prune`], and zeroized.
+
+use alloc::collections::BTreeMap;
+
+use group::GroupEncoding;
+
+use crate::{Suite, VerifyingKey, expand, mode::Mode, server::Server};
+
+/// The identifier of a key.
+///
+/// This is not defined in RFC 9497. The identifier is computed with the hash function of the suite
+/// as `Hash(I2OSP(len(pkS), 2) || pkS || I2OSP(5, 2) || "KeyId" || I2OSP(0, 2) || "Expand")`,
+/// truncated to 32 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyId(pub [u8; 32]);
+
+impl KeyId {
+    /// The identifier of the `verifying_key`.
+    pub fn new<S: Suite>(verifying_key: VerifyingKey<S::Group>) -> Self {
+        let mut id = [0; 32];
+        expand::<S>(verifying_key.0.to_bytes().as_ref(), b"KeyId", &mut id);
+        KeyId(id)
+    }
+}
+
+/// A key and its window of validity.
+struct VersionedKey<S: Suite, M: Mode> {
+    server: Server<S, M>,
+    activation: u64,
+    expiry: u64,
+}
+
+/// The successive keys of a server.
+pub struct KeyManager<S: Suite, M: Mode> {
+    keys: BTreeMap<KeyId, VersionedKey<S, M>>,
+    grace_period: u64,
+}
+
+impl<S: Suite, M: Mode> core::fmt::Debug for KeyManager<S, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KeyManager")
+            .field("keys", &self.keys.keys())
+            .field("grace_period", &self.grace_period)
+            .finish()
+    }
+}
+
+impl<S: Suite, M: Mode> KeyManager<S, M> {
+    /// Create a manager without any key, accepting redemptions for `grace_period` after a key
+    /// expired.
+    pub fn new(grace_period: u64) -> Self {
+        KeyManager {
+            keys: BTreeMap::new(),
+            grace_period,
+        }
+    }
+
+    /// Add the key of the `server`, valid from `activation` until `expiry`, excluded.
+    ///
+    /// If the key was already added, its window is replaced.
+    pub fn insert(
+        &mut self,
+        server: Server<S, M>,
+        activation: u64,
+        expiry: u64,
+    ) -> Result<KeyId, InvalidWindow> {
+        if expiry <= activation {
+            return Err(InvalidWindow);
+        }
+        let id = KeyId::new::<S>(server.verifying_key());
+        self.keys.insert(
+            id,
+            VersionedKey {
+                server,
+                activation,
+                expiry,
+            },
+        );
+        Ok(id)
+    }
+
+    /// The key to use for new evaluations at time `now`: the most recently activated key that has
+    /// not expired.
+    pub fn current(&self, now: u64) -> Option<(KeyId, &Server<S, M>)> {
+        self.keys
+            .iter()
+            .filter(|(_, key)| key.activation <= now && now < key.expiry)
+            .max_by_key(|(_, key)| key.activation)
+            .map(|(id, key)| (*id, &key.server))
+    }
+
+    /// The key `id` to evaluate with at time `now`.
+    pub fn evaluation_key(&self, id: &KeyId, now: u64) -> Result<&Server<S, M>, KeyError> {
+        self.key(id, now, 0)
+    }
+
+    /// The key `id` to redeem with at time `now`, accepted for the grace period after it expired.
+    pub fn redemption_key(&self, id: &KeyId, now: u64) -> Result<&Server<S, M>, KeyError> {
+        self.key(id, now, self.grace_period)
+    }
+
+    /// The verifying key and window of the key `id`.
+    pub fn verifying_key(&self, id: &KeyId) -> Option<(VerifyingKey<S::Group>, u64, u64)> {
+        self.keys
+            .get(id)
+            .map(|key| (key.server.verifying_key(), key.activation, key.expiry))
+    }
+
+    /// The identifiers of the keys, activated or not.
+    pub fn ids(&self) -> impl Iterator<Item = &KeyId> {
+        self.keys.keys()
+    }
+
+    /// Remove, and zeroize, the keys that can't be used for redemptions anymore at time `now`.
+    pub fn prune(&mut self, now: u64) {
+        let grace_period = self.grace_period;
+        self.keys.retain(|_, key| {
+            let keep = now < key.expiry.saturating_add(grace_period);
+            if !keep {
+                zeroize::Zeroize::zeroize(&mut key.server);
+            }
+            keep
+        });
+    }
+
+    /// The keys with their identifier and window.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = (&KeyId, &Server<S, M>, u64, u64)> {
+        self.keys
+            .iter()
+            .map(|(id, key)| (id, &key.server, key.activation, key.expiry))
+    }
+
+    fn key(&self, id: &KeyId, now: u64, grace_period: u64) -> Result<&Server<S, M>, KeyError> {
+        let key = self.keys.get(id).ok_or(KeyError::UnknownKey)?;
+        if now < key.activation {
+            return Err(KeyError::NotYetActive);
+        }
+        if now >= key.expiry.saturating_add(grace_period) {
+            return Err(KeyError::Expired);
+        }
+        Ok(&key.server)
+    }
+}
+
+/// The expiry of a key is not after its activation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidWindow;
+
+impl core::fmt::Display for InvalidWindow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the expiry of the key is not after its activation")
+    }
+}
+
+impl core::error::Error for InvalidWindow {}
+
+/// Error returned when routing a request by [`KeyId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyError {
+    /// No key has this identifier.
+    UnknownKey,
+    /// The key is not activated yet.
+    NotYetActive,
+    /// The key has expired.
+    Expired,
+}
+
+impl core::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KeyError::UnknownKey => write!(f, "no key has this identifier"),
+            KeyError::NotYetActive => write!(f, "the key is not activated yet"),
+            KeyError::Expired => write!(f, "the key has expired"),
+        }
+    }
+}
+
+impl core::error::Error for KeyError {}
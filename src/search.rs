@@ -0,0 +1,101 @@
+//! Keyword token derivation for searchable encryption.
+//!
+//! Encrypted search indexes map keywords to [`SearchToken`]s: the `label` locates the entries of
+//! the keyword in the index, and the `key` encrypts them. Tokens are derived from the output of
+//! the OPRF over the keyword, so that clients never hold the master key of the index and the key
+//! server never learns the keywords being searched.
+//!
+//! The identifier of the index is used as the shared `info` of the [`Partial`] mode, so the tokens
+//! of a keyword are unrelated from one index to another.
+
+use digest::Output;
+use group::Group;
+use rand_core::RngCore;
+
+use crate::{
+    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{Client, InvalidInput, InvalidProof},
+    expand,
+    mode::Partial,
+};
+
+/// The tokens of a keyword in an index.
+///
+/// The label is `L` bytes long and the key is `K` bytes long. Both are zeroized on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SearchToken<const L: usize, const K: usize> {
+    /// Locates the entries of the keyword in the index.
+    pub label: [u8; L],
+    /// Encrypts the entries of the keyword in the index.
+    pub key: [u8; K],
+}
+
+impl<const L: usize, const K: usize> SearchToken<L, K> {
+    /// Derive the tokens of a keyword from the output of the OPRF.
+    pub fn from_output<S: Suite>(output: &Output<S::Hash>) -> Self {
+        let mut token = SearchToken {
+            label: [0; L],
+            key: [0; K],
+        };
+        expand::<S>(output, b"SearchLabel", &mut token.label);
+        expand::<S>(output, b"SearchKey", &mut token.key);
+        token
+    }
+}
+
+impl<const L: usize, const K: usize> zeroize::Zeroize for SearchToken<L, K> {
+    fn zeroize(&mut self) {
+        self.label.zeroize();
+        self.key.zeroize();
+    }
+}
+
+impl<const L: usize, const K: usize> Drop for SearchToken<L, K> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl<const L: usize, const K: usize> zeroize::ZeroizeOnDrop for SearchToken<L, K> {}
+
+impl<const L: usize, const K: usize> core::fmt::Debug for SearchToken<L, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SearchToken")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request for the tokens of `N` keywords to the key server.
+pub struct TokenRequest<'a, 'b, const N: usize, S: Suite> {
+    client: Client<'a, 'b, N, S, Partial>,
+}
+
+impl<'a, 'b, const N: usize, S: Suite> TokenRequest<'a, 'b, N, S> {
+    /// Blind the keywords.
+    ///
+    /// The blinded elements are sent to the key server alongside the `index` identifier, and
+    /// evaluated with
+    /// [`Server<_, Partial>::evaluate`](crate::server::Server#impl-Server<S,+Partial>).
+    #[allow(clippy::type_complexity)]
+    pub fn new(
+        keywords: [Input<'a>; N],
+        index: Input<'b>,
+        verifying_key: VerifyingKey<S::Group>,
+        rng: &mut impl RngCore,
+    ) -> Result<(Self, [Blinded<S::Group>; N]), InvalidInput> {
+        let (client, blinded_elements) =
+            Client::<_, S, Partial>::blind(keywords, index, verifying_key, rng)?;
+        Ok((TokenRequest { client }, blinded_elements))
+    }
+
+    /// Verify the evaluation of the key server, and derive the tokens of the keywords.
+    pub fn finish<const L: usize, const K: usize>(
+        self,
+        evaluated_elements: [Evaluated<S::Group>; N],
+        proof: Proof<<S::Group as Group>::Scalar>,
+    ) -> Result<[SearchToken<L, K>; N], InvalidProof> {
+        let outputs = self.client.finalize(evaluated_elements, proof)?;
+        Ok(outputs.map(|output| SearchToken::from_output::<S>(&output)))
+    }
+}
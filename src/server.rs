@@ -1,12 +1,17 @@
 //! OPRF [`Server`] implementation.
 
+use digest::Output;
 use group::{Group, ff::Field};
 use rand_core::RngCore;
 
 use crate::{
-    Blinded, Evaluated, Input, Proof, Suite, VerifyingKey, context_string, generate_proof,
-    hash_to_scalar,
-    mode::{self, GetVerifyingKey, Mode},
+    Blinded, Commitments, Evaluated, Input, MAX_ELEMENTS, Proof, Suite, VerifyingKey,
+    backend::{KeyBackend, prove_with},
+    challenge,
+    client::InvalidInput,
+    composite_seed, compute_composite, context_string, derive_nonce, finalize_hash, hash_inputs,
+    info_scalar, map_array,
+    mode::{self, Mode, ServerPayload},
 };
 
 /// Server of the OPRF protocol.
@@ -24,26 +29,30 @@ use crate::{
 /// elements as input, and returns an array of [`Evaluated`] elements. If batching is not desired,
 /// simply use an array of length 1.
 ///
+/// The secret key is held in memory by default. A server created with [`Server::from_backend`]
+/// instead delegates the operations on its key to a [`KeyBackend`], and evaluates with the
+/// `try_evaluate` methods.
+///
 /// [`secret_key`]: Server::secret_key
 /// [`verifying_key`]: Server::verifying_key
 /// [`Client`]: crate::client::Client
 /// [Base]: #impl-Server<S,+Base>
 /// [Verifiable]: #impl-Server<S,+Verifiable>
 /// [Partial]: #impl-Server<S,+Partial>
-#[allow(private_bounds)]
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Server<S: Suite, M: Mode> {
-    key: <S::Group as Group>::Scalar,
-    payload: M::ServerPayload<S::Group>,
+pub struct Server<S: Suite, M: Mode, K = SecretKey<<<S as Suite>::Group as Group>::Scalar>> {
+    key: K,
+    payload: M::ServerPayload<S>,
 }
 
 impl<S: Suite, M: Mode> zeroize::Zeroize for Server<S, M> {
     fn zeroize(&mut self) {
-        self.key = <S::Group as Group>::Scalar::ZERO;
+        self.key.zeroize();
     }
 }
 
-#[allow(private_bounds)]
+impl<S: Suite, M: Mode> zeroize::ZeroizeOnDrop for Server<S, M> {}
+
 impl<S: Suite, M: Mode> Server<S, M> {
     /// Initialize a new server with a random secret key.
     ///
@@ -51,12 +60,7 @@ impl<S: Suite, M: Mode> Server<S, M> {
     ///
     /// [`GenerateKeyPair`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.2-2
     pub fn random(rng: &mut impl RngCore) -> Self {
-        let key = <S::Group as Group>::Scalar::random(rng);
-
-        Server {
-            key,
-            payload: crate::mode::_From::_from(&key),
-        }
+        Self::from_secret_key(SecretKey::new(<S::Group as Group>::Scalar::random(rng)))
     }
 
     /// Deterministically initialize a new server.
@@ -79,27 +83,76 @@ impl<S: Suite, M: Mode> Server<S, M> {
             counter = counter.checked_add(1).ok_or(InvalidSeed)?;
         }
 
-        Ok(Server {
-            key: secret_key,
-            payload: crate::mode::_From::_from(&secret_key),
-        })
+        Ok(Self::from_secret_key(SecretKey::new(secret_key)))
     }
 
     /// Initialize a new server from the provided secret key.
     ///
     /// This is not defined in RFC 9497 and not recommended, but is an obvious constructor that
     /// can be useful.
-    pub fn from_secret_key(key: <S::Group as Group>::Scalar) -> Self {
-        Server {
-            key,
-            payload: crate::mode::_From::_from(&key),
-        }
+    pub fn from_secret_key(key: SecretKey<<S::Group as Group>::Scalar>) -> Self {
+        let payload = M::ServerPayload::<S>::from_secret_key(&key.0);
+        debug_assert!(
+            payload
+                .verifying_key()
+                .is_none_or(|verifying_key| verifying_key.0 == S::Group::mul_by_generator(&key.0)),
+            "the payload holds the verifying key of the server"
+        );
+        Server { payload, key }
     }
 
     /// Access the server's secret key.
     ///
-    /// Be careful with it!
-    pub fn secret_key(&self) -> &<S::Group as Group>::Scalar {
+    /// The scalar itself is only accessible with [`SecretKey::expose_secret`].
+    pub fn secret_key(&self) -> &SecretKey<<S::Group as Group>::Scalar> {
+        &self.key
+    }
+
+    /// Split the server into its secret key and its payload.
+    pub(crate) fn into_parts(
+        self,
+    ) -> (SecretKey<<S::Group as Group>::Scalar>, M::ServerPayload<S>) {
+        (self.key, self.payload)
+    }
+
+    /// `Mode` dependent implementation of the `full_evaluate` operation in `mode::Base` and
+    /// `mode::Verifiable`, so that the correct `context_string` is used in each mode.
+    ///
+    /// Specified in [RFC 9497 Section 3.3.1](https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.1)
+    fn full_evaluate_impl<const N: usize>(
+        &self,
+        inputs: [Input<'_>; N],
+    ) -> Result<[Output<S::Hash>; N], InvalidInput> {
+        let key = self.key.0;
+        let input_elements = hash_inputs::<N, S, M>(inputs)?;
+
+        Ok(map_array(|i| {
+            finalize_hash::<S>(inputs[i], None, input_elements[i] * key)
+        }))
+    }
+}
+
+impl<S: Suite, M: Mode, K: KeyBackend<S>> Server<S, M, K> {
+    /// Initialize a new server with its secret key held by the `backend`.
+    ///
+    /// This is not defined in RFC 9497.
+    pub fn from_backend(backend: K) -> Self {
+        let verifying_key = backend.verifying_key();
+        let payload = M::ServerPayload::<S>::from_verifying_key(verifying_key);
+        debug_assert!(
+            payload
+                .verifying_key()
+                .is_none_or(|key| key == verifying_key),
+            "the payload holds the verifying key of the server"
+        );
+        Server {
+            payload,
+            key: backend,
+        }
+    }
+
+    /// Access the backend holding the secret key of the server.
+    pub fn backend(&self) -> &K {
         &self.key
     }
 
@@ -109,8 +162,8 @@ impl<S: Suite, M: Mode> Server<S, M> {
     /// `mode::Partial`.
     pub fn verifying_key(&self) -> VerifyingKey<S::Group> {
         self.payload
-            .get_verifying_key()
-            .unwrap_or_else(|| VerifyingKey(S::Group::mul_by_generator(&self.key)))
+            .verifying_key()
+            .unwrap_or_else(|| self.key.verifying_key())
     }
 }
 
@@ -123,9 +176,24 @@ impl<S: Suite> Server<S, mode::Base> {
     pub fn evaluate<const N: usize>(
         &self,
         blinded_elements: [Blinded<S::Group>; N],
-    ) -> [Evaluated<S::Group>; N]
-where {
-        blinded_elements.map(|Blinded(blinded_element)| Evaluated(blinded_element * self.key))
+    ) -> [Evaluated<S::Group>; N] {
+        in_memory(self.try_evaluate(blinded_elements))
+    }
+
+    /// Evaluate the inputs directly, without the client.
+    ///
+    /// This computes the same outputs as running the protocol with a [`Client`], for inputs that
+    /// the server knows in the clear.
+    ///
+    /// Corresponds to the [`Evaluate`] method defined for OPRFs in RFC 9497.
+    ///
+    /// [`Client`]: crate::client::Client
+    /// [`Evaluate`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.1
+    pub fn full_evaluate<const N: usize>(
+        &self,
+        inputs: [Input<'_>; N],
+    ) -> Result<[Output<S::Hash>; N], InvalidInput> {
+        self.full_evaluate_impl(inputs)
     }
 }
 
@@ -134,6 +202,10 @@ impl<S: Suite> Server<S, mode::Verifiable> {
     ///
     /// The [`BlindEvaluate`] method defined for VOPRFs in RFC 9497.
     ///
+    /// The nonce of the proof is drawn from `rng`, and a weak `rng` can leak the secret key through
+    /// the proofs. See [`evaluate_hedged`](Self::evaluate_hedged) and
+    /// [`evaluate_deterministic`](Self::evaluate_deterministic) to avoid relying on it.
+    ///
     /// [`BlindEvaluate`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.2-2
     #[allow(clippy::type_complexity)]
     pub fn evaluate<const N: usize>(
@@ -141,20 +213,185 @@ impl<S: Suite> Server<S, mode::Verifiable> {
         blinded_elements: [Blinded<S::Group>; N],
         rng: &mut impl RngCore,
     ) -> ([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>) {
-        let evaluated_elements =
-            blinded_elements.map(|Blinded(blinded_element)| Evaluated(blinded_element * self.key));
-        let verifying_key = self.verifying_key();
-        let proof = generate_proof::<N, S, mode::Verifiable>(
-            self.key,
-            S::Group::generator(),
-            verifying_key.0,
-            blinded_elements.map(|Blinded(b)| b),
-            evaluated_elements.map(|Evaluated(e)| e),
-            rng,
-        );
+        in_memory(self.try_evaluate(blinded_elements, rng))
+    }
+
+    /// Evaluate the blinded element and prove the evaluation, with a hedged nonce.
+    ///
+    /// The nonce of the proof is derived from the secret key, the evaluation, and randomness drawn
+    /// from `rng`. The proof stays secure even if `rng` is weak or repeats its outputs, for
+    /// example after a fork. This is not defined in RFC 9497, but the proofs are verified in the
+    /// same way.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_hedged<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        rng: &mut impl RngCore,
+    ) -> ([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>) {
+        let (evaluated_elements, proof, _) = self.evaluate_committed(blinded_elements, rng);
+        (evaluated_elements, proof)
+    }
+
+    /// Evaluate the blinded element and prove the evaluation, with a deterministic nonce.
+    ///
+    /// The nonce of the proof is derived from the secret key and the evaluation only, so the same
+    /// blinded elements always get the same proof. This is not defined in RFC 9497, but the proofs
+    /// are verified in the same way.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_deterministic<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+    ) -> ([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>) {
+        let (evaluated_elements, proof, _) = self
+            .evaluate_in_memory(blinded_elements, |k, m, z| {
+                derive_nonce::<S, mode::Verifiable>(*k, m, z, &[])
+            });
+        (evaluated_elements, proof)
+    }
 
+    /// Evaluate the blinded element and prove the evaluation, with the provided `nonce`.
+    ///
+    /// This is meant to reproduce test vectors. The `nonce` must be uniformly random and never
+    /// reused, otherwise the proofs leak the secret key.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_with_nonce<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        nonce: <S::Group as Group>::Scalar,
+    ) -> ([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>) {
+        let (evaluated_elements, proof, _) =
+            self.evaluate_in_memory(blinded_elements, |_, _, _| nonce);
         (evaluated_elements, proof)
     }
+
+    /// Evaluate the blinded element and prove the evaluation like
+    /// [`evaluate_hedged`](Self::evaluate_hedged), and also return the [`Commitments`] of the
+    /// proof.
+    ///
+    /// Sending the commitments along with the proof lets clients verify many proofs at once with
+    /// [`batch::verify`](crate::batch::verify). This is not defined in RFC 9497.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_committed<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        rng: &mut impl RngCore,
+    ) -> (
+        [Evaluated<S::Group>; N],
+        Proof<<S::Group as Group>::Scalar>,
+        Commitments<S::Group>,
+    ) {
+        let mut randomness = [0; 32];
+        rng.fill_bytes(&mut randomness);
+        self.evaluate_in_memory(blinded_elements, |k, m, z| {
+            derive_nonce::<S, mode::Verifiable>(*k, m, z, &randomness)
+        })
+    }
+
+    /// [`evaluate_with`](Self::evaluate_with) with the secret key in memory, and the nonce
+    /// computed by `nonce(k, m, z)` from the secret key and the composites.
+    #[allow(clippy::type_complexity)]
+    fn evaluate_in_memory<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        nonce: impl FnOnce(
+            &<S::Group as Group>::Scalar,
+            S::Group,
+            S::Group,
+        ) -> <S::Group as Group>::Scalar,
+    ) -> (
+        [Evaluated<S::Group>; N],
+        Proof<<S::Group as Group>::Scalar>,
+        Commitments<S::Group>,
+    ) {
+        let (evaluated_elements, proof, commitments) =
+            in_memory(self.evaluate_with(blinded_elements, |key, m, challenge| {
+                Ok(prove_with::<S>(key.0, m, challenge, nonce))
+            }));
+        let commitments = commitments.expect("the challenge is computed in memory");
+
+        (evaluated_elements, proof, commitments)
+    }
+
+    /// Evaluate the inputs directly, without the client.
+    ///
+    /// This computes the same outputs as running the protocol with a [`Client`], for inputs that
+    /// the server knows in the clear.
+    ///
+    /// Corresponds to the [`Evaluate`] method defined for VOPRFs in RFC 9497.
+    ///
+    /// [`Client`]: crate::client::Client
+    /// [`Evaluate`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.2
+    pub fn full_evaluate<const N: usize>(
+        &self,
+        inputs: [Input<'_>; N],
+    ) -> Result<[Output<S::Hash>; N], InvalidInput> {
+        self.full_evaluate_impl(inputs)
+    }
+}
+
+impl<S: Suite, K: KeyBackend<S>> Server<S, mode::Verifiable, K> {
+    /// Evaluate the blinded element with the backend, and prove the evaluation.
+    ///
+    /// Like [`evaluate`](Server#impl-Server<S,+Verifiable>), with the nonce drawn by the backend,
+    /// but fails with the errors of the backend.
+    #[allow(clippy::type_complexity)]
+    pub fn try_evaluate<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), K::Error> {
+        let (evaluated_elements, proof, _) = self
+            .evaluate_with(blinded_elements, |key, m, challenge| {
+                key.prove(&<S::Group as Group>::Scalar::ZERO, m, challenge, rng)
+            })?;
+        Ok((evaluated_elements, proof))
+    }
+
+    /// Code shared between the `evaluate` methods, with the proof computed by
+    /// `prove(key, m, challenge)` as in [`KeyBackend::prove`].
+    ///
+    /// Also returns the commitments of the proof if `challenge` was called.
+    #[allow(clippy::type_complexity)]
+    fn evaluate_with<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        prove: impl FnOnce(
+            &K,
+            S::Group,
+            &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+        ) -> Result<Proof<<S::Group as Group>::Scalar>, K::Error>,
+    ) -> Result<
+        (
+            [Evaluated<S::Group>; N],
+            Proof<<S::Group as Group>::Scalar>,
+            Option<Commitments<S::Group>>,
+        ),
+        K::Error,
+    > {
+        const { assert!(N <= MAX_ELEMENTS, "a proof covers at most 2^16 elements") };
+        let blinded_elements = blinded_elements.map(|Blinded(b)| b);
+        let mut evaluated_elements = blinded_elements;
+        self.key.multiply(&mut evaluated_elements)?;
+
+        let verifying_key = self.payload.verifying_key.0;
+        let m = compute_composite::<S, mode::Verifiable>(
+            &self.payload.seed,
+            &blinded_elements,
+            &evaluated_elements,
+        );
+        let mut commitments = None;
+        let proof = prove(&self.key, m, &mut |z, t2, t3| {
+            commitments = Some(Commitments { m, z, t2, t3 });
+            challenge::<S, mode::Verifiable>(verifying_key, m, z, t2, t3)
+        })?;
+
+        Ok((evaluated_elements.map(Evaluated), proof, commitments))
+    }
+
+    /// The seed of the composites of the proofs of the server.
+    pub(crate) fn composite_seed(&self) -> &Output<S::Hash> {
+        &self.payload.seed
+    }
 }
 
 impl<S: Suite> Server<S, mode::Partial> {
@@ -162,6 +399,10 @@ impl<S: Suite> Server<S, mode::Partial> {
     ///
     /// The [`BlindEvaluate`] method defined for POPRFs in RFC 9497.
     ///
+    /// The nonce of the proof is drawn from `rng`, and a weak `rng` can leak the secret key through
+    /// the proofs. See [`evaluate_hedged`](Self::evaluate_hedged) and
+    /// [`evaluate_deterministic`](Self::evaluate_deterministic) to avoid relying on it.
+    ///
     /// [`BlindEvaluate`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.3-4
     #[allow(clippy::type_complexity)]
     pub fn evaluate<const N: usize>(
@@ -171,32 +412,290 @@ impl<S: Suite> Server<S, mode::Partial> {
         rng: &mut impl RngCore,
     ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), UndefinedInverse>
     {
-        let framed_info = [
-            b"Info".as_slice(),
-            &(info.as_ref().len() as u16).to_be_bytes(),
-            info.as_ref(),
-        ];
-        let m = hash_to_scalar::<S, mode::Partial>(&framed_info);
-        let t = self.key + m;
+        self.try_evaluate(blinded_elements, info, rng)
+    }
+
+    /// Evaluate the partially blinded element and prove the evaluation, with a hedged nonce.
+    ///
+    /// The nonce of the proof is derived from the secret key, the `info`, the evaluation, and
+    /// randomness drawn from `rng`. The proof stays secure even if `rng` is weak or repeats its
+    /// outputs, for example after a fork. This is not defined in RFC 9497, but the proofs are
+    /// verified in the same way.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_hedged<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), UndefinedInverse>
+    {
+        let (evaluated_elements, proof, _) =
+            self.evaluate_committed(blinded_elements, info, rng)?;
+        Ok((evaluated_elements, proof))
+    }
+
+    /// Evaluate the partially blinded element and prove the evaluation, with a deterministic
+    /// nonce.
+    ///
+    /// The nonce of the proof is derived from the secret key, the `info` and the evaluation only,
+    /// so the same blinded elements and `info` always get the same proof. This is not defined in
+    /// RFC 9497, but the proofs are verified in the same way.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_deterministic<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), UndefinedInverse>
+    {
+        let (evaluated_elements, proof, _) =
+            self.evaluate_in_memory(blinded_elements, info, |t, m, z| {
+                derive_nonce::<S, mode::Partial>(*t, m, z, &[])
+            })?;
+        Ok((evaluated_elements, proof))
+    }
+
+    /// Evaluate the partially blinded element and prove the evaluation, with the provided `nonce`.
+    ///
+    /// This is meant to reproduce test vectors. The `nonce` must be uniformly random and never
+    /// reused, otherwise the proofs leak the secret key.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_with_nonce<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        nonce: <S::Group as Group>::Scalar,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), UndefinedInverse>
+    {
+        let (evaluated_elements, proof, _) =
+            self.evaluate_in_memory(blinded_elements, info, |_, _, _| nonce)?;
+        Ok((evaluated_elements, proof))
+    }
+
+    /// Evaluate the partially blinded element and prove the evaluation like
+    /// [`evaluate_hedged`](Self::evaluate_hedged), and also return the [`Commitments`] of the
+    /// proof.
+    ///
+    /// Sending the commitments along with the proof lets clients verify many proofs at once with
+    /// [`batch::verify`](crate::batch::verify). This is not defined in RFC 9497.
+    #[allow(clippy::type_complexity)]
+    pub fn evaluate_committed<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Result<
+        (
+            [Evaluated<S::Group>; N],
+            Proof<<S::Group as Group>::Scalar>,
+            Commitments<S::Group>,
+        ),
+        UndefinedInverse,
+    > {
+        let mut randomness = [0; 32];
+        rng.fill_bytes(&mut randomness);
+        self.evaluate_in_memory(blinded_elements, info, |t, m, z| {
+            derive_nonce::<S, mode::Partial>(*t, m, z, &randomness)
+        })
+    }
+
+    /// [`evaluate_with`](Self::evaluate_with) with the secret key in memory, and the nonce
+    /// computed by `nonce(t, m, z)` from the tweaked secret key and the composites.
+    #[allow(clippy::type_complexity)]
+    fn evaluate_in_memory<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        nonce: impl FnOnce(
+            &<S::Group as Group>::Scalar,
+            S::Group,
+            S::Group,
+        ) -> <S::Group as Group>::Scalar,
+    ) -> Result<
+        (
+            [Evaluated<S::Group>; N],
+            Proof<<S::Group as Group>::Scalar>,
+            Commitments<S::Group>,
+        ),
+        UndefinedInverse,
+    > {
+        let (evaluated_elements, proof, commitments) =
+            self.evaluate_with(blinded_elements, info, |key, tweak, m, challenge| {
+                Ok(prove_with::<S>(key.0 + tweak, m, challenge, nonce))
+            })?;
+        let commitments = commitments.expect("the challenge is computed in memory");
+
+        Ok((evaluated_elements, proof, commitments))
+    }
+
+    /// Evaluate the inputs directly with the shared `info`, without the client.
+    ///
+    /// This computes the same outputs as running the protocol with a [`Client`], for inputs that
+    /// the server knows in the clear.
+    ///
+    /// Corresponds to the [`Evaluate`] method defined for POPRFs in RFC 9497.
+    ///
+    /// [`Client`]: crate::client::Client
+    /// [`Evaluate`]: https://www.rfc-editor.org/rfc/rfc9497.html#section-3.3.3
+    pub fn full_evaluate<const N: usize>(
+        &self,
+        inputs: [Input<'_>; N],
+        info: Input<'_>,
+    ) -> Result<[Output<S::Hash>; N], EvaluationError> {
+        let m = info_scalar::<S>(info);
+        let t = self.key.0 + m;
         let t_inv = t.invert().into_option().ok_or(UndefinedInverse)?;
 
-        let evaluated_elements =
-            blinded_elements.map(|Blinded(blinded_element)| Evaluated(blinded_element * t_inv));
-
-        let tweaked_key = S::Group::mul_by_generator(&t);
-        let proof = generate_proof::<N, S, mode::Partial>(
-            t,
-            S::Group::generator(),
-            tweaked_key,
-            evaluated_elements.map(|Evaluated(e)| e),
-            blinded_elements.map(|Blinded(b)| b),
-            rng,
-        );
+        let input_elements = hash_inputs::<N, S, mode::Partial>(inputs)?;
+
+        Ok(map_array(|i| {
+            finalize_hash::<S>(inputs[i], Some(info), input_elements[i] * t_inv)
+        }))
+    }
+}
 
+impl<S: Suite, K: KeyBackend<S>> Server<S, mode::Partial, K> {
+    /// Evaluate the partially blinded element with the backend, and prove the evaluation.
+    ///
+    /// Like [`evaluate`](Server#impl-Server<S,+Partial>), with the nonce drawn by the backend, but
+    /// fails with the errors of the backend.
+    #[allow(clippy::type_complexity)]
+    pub fn try_evaluate<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), K::Error> {
+        let (evaluated_elements, proof, _) =
+            self.evaluate_with(blinded_elements, info, |key, tweak, m, challenge| {
+                key.prove(tweak, m, challenge, rng)
+            })?;
         Ok((evaluated_elements, proof))
     }
+
+    /// Code shared between the `evaluate` methods, with the proof computed by
+    /// `prove(key, tweak, m, challenge)` as in [`KeyBackend::prove`].
+    ///
+    /// Also returns the commitments of the proof if `challenge` was called.
+    #[allow(clippy::type_complexity)]
+    fn evaluate_with<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+        info: Input<'_>,
+        prove: impl FnOnce(
+            &K,
+            &<S::Group as Group>::Scalar,
+            S::Group,
+            &mut dyn FnMut(S::Group, S::Group, S::Group) -> <S::Group as Group>::Scalar,
+        ) -> Result<Proof<<S::Group as Group>::Scalar>, K::Error>,
+    ) -> Result<
+        (
+            [Evaluated<S::Group>; N],
+            Proof<<S::Group as Group>::Scalar>,
+            Option<Commitments<S::Group>>,
+        ),
+        K::Error,
+    > {
+        const { assert!(N <= MAX_ELEMENTS, "a proof covers at most 2^16 elements") };
+        let tweak = info_scalar::<S>(info);
+        let blinded_elements = blinded_elements.map(|Blinded(b)| b);
+        let mut evaluated_elements = blinded_elements;
+        self.key.multiply_inverse(&tweak, &mut evaluated_elements)?;
+
+        let tweaked_key = S::Group::mul_by_generator(&tweak) + self.payload.0;
+        let m = compute_composite::<S, mode::Partial>(
+            &composite_seed::<S, mode::Partial>(tweaked_key),
+            &evaluated_elements,
+            &blinded_elements,
+        );
+        let mut commitments = None;
+        let proof = prove(&self.key, &tweak, m, &mut |z, t2, t3| {
+            commitments = Some(Commitments { m, z, t2, t3 });
+            challenge::<S, mode::Partial>(tweaked_key, m, z, t2, t3)
+        })?;
+
+        Ok((evaluated_elements.map(Evaluated), proof, commitments))
+    }
+}
+
+impl<S: Suite, K: KeyBackend<S>> Server<S, mode::Base, K> {
+    /// Evaluate the blinded element with the backend.
+    ///
+    /// Like [`evaluate`](Server#impl-Server<S,+Base>), but fails with the errors of the backend.
+    pub fn try_evaluate<const N: usize>(
+        &self,
+        blinded_elements: [Blinded<S::Group>; N],
+    ) -> Result<[Evaluated<S::Group>; N], K::Error> {
+        let mut elements = blinded_elements.map(|Blinded(b)| b);
+        self.key.multiply(&mut elements)?;
+        Ok(elements.map(Evaluated))
+    }
 }
 
+/// The result of an operation on a secret key held in memory, which only fails when inverting a
+/// tweaked key.
+fn in_memory<T>(result: Result<T, UndefinedInverse>) -> T {
+    result.expect("multiplying by the secret key never fails")
+}
+
+/// The secret key of a [`Server`].
+///
+/// The key is zeroized on drop, redacted from the [`Debug`](core::fmt::Debug) output, and compared
+/// in constant time. The scalar is only accessible explicitly, with [`SecretKey::expose_secret`].
+#[derive(Clone)]
+pub struct SecretKey<F: Field>(F);
+
+impl<F: Field> SecretKey<F> {
+    /// Wrap the scalar of a secret key.
+    pub fn new(scalar: F) -> Self {
+        SecretKey(scalar)
+    }
+
+    /// Access the scalar of the secret key.
+    ///
+    /// Be careful with it!
+    pub fn expose_secret(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: Field> zeroize::Zeroize for SecretKey<F> {
+    fn zeroize(&mut self) {
+        // A volatile write followed by a fence, as in the `zeroize` crate, so that the store is not
+        // removed when the key is about to be dropped.
+        // SAFETY: `self.0` is a valid and aligned `F`, and `F: Copy` has no destructor to skip.
+        unsafe { core::ptr::write_volatile(&mut self.0, F::ZERO) };
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+impl<F: Field> Drop for SecretKey<F> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl<F: Field> zeroize::ZeroizeOnDrop for SecretKey<F> {}
+
+impl<F: Field> core::fmt::Debug for SecretKey<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl<F: Field> subtle::ConstantTimeEq for SecretKey<F> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<F: Field> PartialEq for SecretKey<F> {
+    fn eq(&self, other: &Self) -> bool {
+        subtle::ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
+impl<F: Field> Eq for SecretKey<F> {}
+
 /// Deterministic server creation error.
 ///
 /// Creating a server with the provided `seed` and `info` results in an invalid secret key.
@@ -237,3 +736,44 @@ impl core::fmt::Display for UndefinedInverse {
 }
 
 impl core::error::Error for UndefinedInverse {}
+
+/// Error returned by [`Server<_, Partial>::full_evaluate`][Partial].
+///
+/// [Partial]: Server#impl-Server<S,+Partial>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EvaluationError {
+    /// One of the inputs hashes to the identity element.
+    InvalidInput(InvalidInput),
+    /// The server's secret key combined with the `info` results in a zero scalar.
+    UndefinedInverse(UndefinedInverse),
+}
+
+impl From<InvalidInput> for EvaluationError {
+    fn from(error: InvalidInput) -> Self {
+        EvaluationError::InvalidInput(error)
+    }
+}
+
+impl From<UndefinedInverse> for EvaluationError {
+    fn from(error: UndefinedInverse) -> Self {
+        EvaluationError::UndefinedInverse(error)
+    }
+}
+
+impl core::fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EvaluationError::InvalidInput(e) => e.fmt(f),
+            EvaluationError::UndefinedInverse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for EvaluationError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            EvaluationError::InvalidInput(e) => Some(e),
+            EvaluationError::UndefinedInverse(e) => Some(e),
+        }
+    }
+}
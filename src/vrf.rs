@@ -0,0 +1,141 @@
+//! Publicly verifiable evaluation of known inputs.
+//!
+//! In the [`Verifiable`] and [`Partial`] modes, the server can evaluate an input it knows in the
+//! clear and prove the evaluation with the same DLEQ [`Proof`] as in the protocol. Anyone holding
+//! the [`VerifyingKey`] of the server can then check the evaluation with [`verify`] or
+//! [`verify_partial`], and compute the output. This turns the key of the server into a
+//! VRF-like primitive.
+//!
+//! This is not defined in RFC 9497, but the outputs are the same as the ones of the protocol, and
+//! of [`Server::full_evaluate`].
+//!
+//! [`Server::full_evaluate`]: Server#method.full_evaluate
+
+use digest::Output;
+use group::{Group, ff::Field};
+use rand_core::RngCore;
+
+use crate::{
+    Evaluated, Input, Proof, Suite, VerifyingKey,
+    client::{InvalidInput, InvalidProof},
+    finalize_hash, generate_proof, generate_proof_with_seed, hash_inputs, info_scalar, map_array,
+    mode::{Partial, Verifiable},
+    server::{EvaluationError, Server, UndefinedInverse},
+    verify_proof,
+};
+
+impl<S: Suite> Server<S, Verifiable> {
+    /// Evaluate the inputs in the clear and prove the evaluation.
+    ///
+    /// The evaluation can be checked by anyone holding the [`VerifyingKey`] of the server with
+    /// [`verify`].
+    #[allow(clippy::type_complexity)]
+    pub fn prove<const N: usize>(
+        &self,
+        inputs: [Input<'_>; N],
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), InvalidInput> {
+        let key = *self.secret_key().expose_secret();
+        let input_elements = hash_inputs::<N, S, Verifiable>(inputs)?;
+        let evaluated_elements: [S::Group; N] = map_array(|i| input_elements[i] * key);
+        let (proof, _) = generate_proof_with_seed::<S, Verifiable>(
+            key,
+            S::Group::generator(),
+            self.verifying_key().0,
+            self.composite_seed(),
+            &input_elements,
+            &evaluated_elements,
+            |_, _| <S::Group as Group>::Scalar::random(rng),
+        );
+
+        Ok((evaluated_elements.map(Evaluated), proof))
+    }
+}
+
+impl<S: Suite> Server<S, Partial> {
+    /// Evaluate the inputs in the clear with the shared `info`, and prove the evaluation.
+    ///
+    /// The evaluation can be checked by anyone holding the [`VerifyingKey`] of the server with
+    /// [`verify_partial`].
+    #[allow(clippy::type_complexity)]
+    pub fn prove<const N: usize>(
+        &self,
+        inputs: [Input<'_>; N],
+        info: Input<'_>,
+        rng: &mut impl RngCore,
+    ) -> Result<([Evaluated<S::Group>; N], Proof<<S::Group as Group>::Scalar>), EvaluationError>
+    {
+        let t = *self.secret_key().expose_secret() + info_scalar::<S>(info);
+        let t_inv = t.invert().into_option().ok_or(UndefinedInverse)?;
+
+        let input_elements = hash_inputs::<N, S, Partial>(inputs)?;
+        let evaluated_elements: [S::Group; N] = map_array(|i| input_elements[i] * t_inv);
+        let proof = generate_proof::<S, Partial>(
+            t,
+            S::Group::generator(),
+            S::Group::mul_by_generator(&t),
+            &evaluated_elements,
+            &input_elements,
+            rng,
+        );
+
+        Ok((evaluated_elements.map(Evaluated), proof))
+    }
+}
+
+/// Verify an evaluation produced by [`Server<_, Verifiable>::prove`], and compute the outputs.
+///
+/// [`Server<_, Verifiable>::prove`]: Server#method.prove
+pub fn verify<const N: usize, S: Suite>(
+    verifying_key: VerifyingKey<S::Group>,
+    inputs: [Input<'_>; N],
+    evaluated_elements: [Evaluated<S::Group>; N],
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> Result<[Output<S::Hash>; N], InvalidProof> {
+    let input_elements =
+        hash_inputs::<N, S, Verifiable>(inputs).map_err(|InvalidInput| InvalidProof)?;
+    if !verify_proof::<S, Verifiable>(
+        S::Group::generator(),
+        verifying_key.0,
+        &input_elements,
+        &evaluated_elements.map(|e| e.0),
+        proof,
+    ) {
+        return Err(InvalidProof);
+    }
+
+    Ok(map_array(|i| {
+        finalize_hash::<S>(inputs[i], None, evaluated_elements[i].0)
+    }))
+}
+
+/// Verify an evaluation produced by [`Server<_, Partial>::prove`], and compute the outputs.
+///
+/// [`Server<_, Partial>::prove`]: Server#method.prove-1
+pub fn verify_partial<const N: usize, S: Suite>(
+    verifying_key: VerifyingKey<S::Group>,
+    inputs: [Input<'_>; N],
+    info: Input<'_>,
+    evaluated_elements: [Evaluated<S::Group>; N],
+    proof: Proof<<S::Group as Group>::Scalar>,
+) -> Result<[Output<S::Hash>; N], InvalidProof> {
+    let tweaked_key = S::Group::mul_by_generator(&info_scalar::<S>(info)) + verifying_key.0;
+    if tweaked_key.is_identity().into() {
+        return Err(InvalidProof);
+    }
+    let input_elements =
+        hash_inputs::<N, S, Partial>(inputs).map_err(|InvalidInput| InvalidProof)?;
+    if !verify_proof::<S, Partial>(
+        S::Group::generator(),
+        tweaked_key,
+        &evaluated_elements.map(|e| e.0),
+        &input_elements,
+        proof,
+    ) {
+        return Err(InvalidProof);
+    }
+
+    Ok(map_array(|i| {
+        finalize_hash::<S>(inputs[i], Some(info), evaluated_elements[i].0)
+    }))
+}
@@ -0,0 +1,200 @@
+use std::cell::Cell;
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use group::{Group, ff::Field};
+use oprf::{
+    Input, Proof, VerifyingKey,
+    backend::KeyBackend,
+    client::Client,
+    mode::{Base, Partial, Verifiable},
+    server::{Server, UndefinedInverse},
+};
+use rand_core::RngCore;
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn base() {
+    let server = Server::<Ristretto255, Base>::random(&mut Rng::new(1));
+    let backend = server.secret_key().clone();
+    let backend_server = Server::<Ristretto255, Base, _>::from_backend(&backend);
+    assert_eq!(backend_server.verifying_key(), server.verifying_key());
+    assert_eq!(*backend_server.backend(), server.secret_key());
+
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let (_, blinded_elements) =
+        Client::<_, Ristretto255, Base>::blind(inputs, &mut Rng::new(2)).unwrap();
+    assert_eq!(
+        backend_server.try_evaluate(blinded_elements).unwrap(),
+        server.evaluate(blinded_elements)
+    );
+}
+
+#[test]
+fn verifiable() {
+    let server = Server::<P256, Verifiable>::random(&mut Rng::new(1));
+    let backend = server.secret_key().clone();
+    let backend_server = Server::<P256, Verifiable, _>::from_backend(&backend);
+    assert_eq!(backend_server.verifying_key(), server.verifying_key());
+
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let (_, blinded_elements) =
+        Client::<_, P256, Verifiable>::blind(inputs, server.verifying_key(), &mut Rng::new(2))
+            .unwrap();
+    assert_eq!(
+        backend_server
+            .try_evaluate(blinded_elements, &mut Rng::new(3))
+            .unwrap(),
+        server.evaluate(blinded_elements, &mut Rng::new(3))
+    );
+}
+
+#[test]
+fn partial() {
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let backend = server.secret_key().clone();
+    let backend_server = Server::<Ristretto255, Partial, _>::from_backend(&backend);
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let (_, blinded_elements) = Client::<_, Ristretto255, Partial>::blind(
+        inputs,
+        info,
+        server.verifying_key(),
+        &mut Rng::new(2),
+    )
+    .unwrap();
+    assert_eq!(
+        backend_server
+            .try_evaluate(blinded_elements, info, &mut Rng::new(3))
+            .unwrap(),
+        server
+            .evaluate(blinded_elements, info, &mut Rng::new(3))
+            .unwrap()
+    );
+}
+
+/// A keystore that only allows a limited number of operations.
+struct Keystore {
+    key: Scalar,
+    remaining: Cell<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+enum KeystoreError {
+    UndefinedInverse,
+    Exhausted,
+}
+
+impl From<UndefinedInverse> for KeystoreError {
+    fn from(_: UndefinedInverse) -> Self {
+        KeystoreError::UndefinedInverse
+    }
+}
+
+impl Keystore {
+    fn consume(&self) -> Result<(), KeystoreError> {
+        let remaining = self.remaining.get();
+        if remaining == 0 {
+            return Err(KeystoreError::Exhausted);
+        }
+        self.remaining.set(remaining - 1);
+        Ok(())
+    }
+}
+
+impl KeyBackend<Ristretto255> for Keystore {
+    type Error = KeystoreError;
+
+    fn verifying_key(&self) -> VerifyingKey<RistrettoPoint> {
+        VerifyingKey(RistrettoPoint::mul_by_generator(&self.key))
+    }
+
+    fn multiply(&self, elements: &mut [RistrettoPoint]) -> Result<(), KeystoreError> {
+        self.consume()?;
+        elements.iter_mut().for_each(|e| *e *= self.key);
+        Ok(())
+    }
+
+    fn multiply_inverse(
+        &self,
+        tweak: &Scalar,
+        elements: &mut [RistrettoPoint],
+    ) -> Result<(), KeystoreError> {
+        self.consume()?;
+        let t_inv = Field::invert(&(self.key + tweak))
+            .into_option()
+            .ok_or(UndefinedInverse)?;
+        elements.iter_mut().for_each(|e| *e *= t_inv);
+        Ok(())
+    }
+
+    fn prove(
+        &self,
+        tweak: &Scalar,
+        m: RistrettoPoint,
+        challenge: &mut dyn FnMut(RistrettoPoint, RistrettoPoint, RistrettoPoint) -> Scalar,
+        rng: &mut dyn RngCore,
+    ) -> Result<Proof<Scalar>, KeystoreError> {
+        self.consume()?;
+        let t = self.key + tweak;
+        let r = <Scalar as Field>::random(rng);
+        let c = challenge(m * t, RistrettoPoint::mul_by_generator(&r), m * r);
+        Ok(Proof { c, s: r - c * t })
+    }
+}
+
+#[test]
+fn custom_backend() {
+    let mut rng = Rng::new(1);
+    let keystore = Keystore {
+        key: <Scalar as Field>::random(&mut rng),
+        remaining: Cell::new(2),
+    };
+    // The backend is chosen at runtime.
+    let backend: &dyn KeyBackend<Ristretto255, Error = KeystoreError> = &keystore;
+    let server = Server::<Ristretto255, Partial, _>::from_backend(backend);
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+
+    let (client, blinded_elements) =
+        Client::<_, Ristretto255, Partial>::blind(inputs, info, server.verifying_key(), &mut rng)
+            .unwrap();
+    let (evaluated_elements, proof) = server
+        .try_evaluate(blinded_elements, info, &mut rng)
+        .unwrap();
+    client.finalize(evaluated_elements, proof).unwrap();
+
+    assert_eq!(
+        server
+            .try_evaluate(blinded_elements, info, &mut rng)
+            .unwrap_err(),
+        KeystoreError::Exhausted
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn boxed_backend() {
+    let key = oprf::server::SecretKey::new(<Scalar as Field>::random(&mut Rng::new(1)));
+    let server = Server::<Ristretto255, Verifiable>::from_secret_key(key.clone());
+    let backend: Box<dyn KeyBackend<Ristretto255, Error = UndefinedInverse>> = Box::new(key);
+    let backend_server = Server::<Ristretto255, Verifiable, _>::from_backend(backend);
+
+    let input = Input::try_from(b"alpha".as_slice()).unwrap();
+    let (client, blinded_elements) = Client::<_, Ristretto255, Verifiable>::blind(
+        [input],
+        backend_server.verifying_key(),
+        &mut Rng::new(2),
+    )
+    .unwrap();
+    let (evaluated_elements, proof) = backend_server
+        .try_evaluate(blinded_elements, &mut Rng::new(3))
+        .unwrap();
+    assert_eq!(
+        (evaluated_elements, proof),
+        server.evaluate(blinded_elements, &mut Rng::new(3))
+    );
+    client.finalize(evaluated_elements, proof).unwrap();
+}
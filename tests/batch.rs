@@ -0,0 +1,177 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input,
+    batch::{self, BatchError},
+    client::Client,
+    mode::{Partial, Verifiable},
+    receipt::Receipt,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+fn verifiable_receipt(
+    server: &Server<Ristretto255, Verifiable>,
+    rng: &mut Rng,
+) -> Receipt<Ristretto255> {
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let (_, blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind(inputs, server.verifying_key(), rng).unwrap();
+    let (evaluated_elements, proof, commitments) = server.evaluate_committed(blinded_elements, rng);
+    Receipt {
+        verifying_key: server.verifying_key(),
+        blinded_elements: blinded_elements.to_vec(),
+        evaluated_elements: evaluated_elements.to_vec(),
+        info: None,
+        proof,
+        commitments: Some(commitments),
+    }
+}
+
+fn partial_receipt(
+    server: &Server<Ristretto255, Partial>,
+    info: &[u8],
+    rng: &mut Rng,
+) -> Receipt<Ristretto255> {
+    let info_input = Input::try_from(info).unwrap();
+    let (_, blinded_elements) = Client::<_, Ristretto255, Partial>::blind(
+        [Input::try_from(b"alpha".as_slice()).unwrap()],
+        info_input,
+        server.verifying_key(),
+        rng,
+    )
+    .unwrap();
+    let (evaluated_elements, proof, commitments) = server
+        .evaluate_committed(blinded_elements, info_input, rng)
+        .unwrap();
+    Receipt {
+        verifying_key: server.verifying_key(),
+        blinded_elements: blinded_elements.to_vec(),
+        evaluated_elements: evaluated_elements.to_vec(),
+        info: Some(info.to_vec()),
+        proof,
+        commitments: Some(commitments),
+    }
+}
+
+fn receipts(rng: &mut Rng) -> Vec<Receipt<Ristretto255>> {
+    let verifiable = [
+        Server::<Ristretto255, Verifiable>::random(&mut *rng),
+        Server::random(&mut *rng),
+    ];
+    let partial = Server::<Ristretto255, Partial>::random(&mut *rng);
+
+    let mut receipts = Vec::new();
+    for i in 0..4 {
+        receipts.push(verifiable_receipt(&verifiable[i % 2], rng));
+        receipts.push(partial_receipt(
+            &partial,
+            if i % 2 == 0 { b"even" } else { b"odd" },
+            rng,
+        ));
+    }
+    receipts
+}
+
+#[test]
+fn valid_batch() {
+    let mut rng = Rng::new(1);
+    let mut receipts = receipts(&mut rng);
+    assert_eq!(batch::verify(&receipts, &mut rng), Ok(()));
+    assert_eq!(batch::verify::<Ristretto255>(&[], &mut rng), Ok(()));
+
+    // Receipts without commitments are checked on their own.
+    receipts[3].commitments = None;
+    assert_eq!(batch::verify(&receipts, &mut rng), Ok(()));
+}
+
+#[test]
+fn reports_invalid_proof() {
+    let mut rng = Rng::new(1);
+    let mut receipts = receipts(&mut rng);
+
+    receipts[5].info = Some(b"other".to_vec());
+    assert_eq!(
+        batch::verify(&receipts, &mut rng),
+        Err(BatchError { index: 5 })
+    );
+
+    receipts[2].proof = receipts[0].proof;
+    assert_eq!(
+        batch::verify(&receipts, &mut rng),
+        Err(BatchError { index: 2 })
+    );
+}
+
+#[test]
+fn reports_invalid_equation() {
+    let mut rng = Rng::new(1);
+    let mut receipts = receipts(&mut rng);
+
+    // The challenge matches the commitments, but not the composites of the elements: only the
+    // combination detects it.
+    receipts[4].evaluated_elements.swap(0, 1);
+    assert_eq!(
+        batch::verify(&receipts, &mut rng),
+        Err(BatchError { index: 4 })
+    );
+    receipts[7].blinded_elements = receipts[1].blinded_elements.clone();
+    assert_eq!(
+        batch::verify(&receipts, &mut rng),
+        Err(BatchError { index: 4 })
+    );
+
+    let mut receipts = self::receipts(&mut rng);
+    let mut commitments = receipts[6].commitments.unwrap();
+    commitments.t2 = commitments.t3;
+    receipts[6].commitments = Some(commitments);
+    assert_eq!(
+        batch::verify(&receipts, &mut rng),
+        Err(BatchError { index: 6 })
+    );
+}
+
+#[test]
+fn large_batch() {
+    let mut rng = Rng::new(1);
+    let server = Server::<P256, Verifiable>::random(&mut rng);
+    let inputs: [_; 100] = core::array::from_fn(|i| i.to_be_bytes());
+    let inputs = inputs
+        .each_ref()
+        .map(|i| Input::try_from(i.as_slice()).unwrap());
+    let (client, blinded_elements) =
+        Client::<_, P256, Verifiable>::blind(inputs, server.verifying_key(), &mut rng).unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, &mut rng);
+
+    let mut receipt = Receipt::<P256> {
+        verifying_key: server.verifying_key(),
+        blinded_elements: blinded_elements.to_vec(),
+        evaluated_elements: evaluated_elements.to_vec(),
+        info: None,
+        proof,
+        commitments: None,
+    };
+    assert_eq!(
+        client.finalize(evaluated_elements, proof).map(|_| ()),
+        Ok(())
+    );
+    receipt.evaluated_elements.swap(40, 90);
+    assert_eq!(
+        batch::verify(&[receipt.clone()], &mut rng),
+        Err(BatchError { index: 0 })
+    );
+
+    let (evaluated_elements, proof, commitments) =
+        server.evaluate_committed(blinded_elements, &mut rng);
+    receipt.evaluated_elements = evaluated_elements.to_vec();
+    receipt.proof = proof;
+    receipt.commitments = Some(commitments);
+    assert_eq!(batch::verify(&[receipt.clone()], &mut rng), Ok(()));
+    receipt.evaluated_elements.swap(40, 90);
+    assert_eq!(
+        batch::verify(&[receipt], &mut rng),
+        Err(BatchError { index: 0 })
+    );
+}
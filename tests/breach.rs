@@ -0,0 +1,83 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input,
+    breach::{Bucket, Check, Database},
+    mode::Partial,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+const LEAKED: [&[u8]; 4] = [
+    b"alice:hunter2",
+    b"bob:password",
+    b"carol:123456",
+    b"dave:letmein",
+];
+
+fn check<S: oprf::Suite, const L: usize>(database: &Database<S, L>, credential: &[u8]) -> bool {
+    let mut rng = Rng::new(7);
+    let credential = Input::try_from(credential).unwrap();
+    let bucket = Bucket::<L>::of::<S>(credential);
+    let (check, blinded_element) =
+        Check::<S>::new(credential, &bucket, database.verifying_key(), &mut rng).unwrap();
+
+    let (evaluated_element, proof) = database
+        .evaluate(blinded_element, &bucket, &mut rng)
+        .unwrap();
+    check
+        .finish(evaluated_element, proof, database.bucket(&bucket))
+        .unwrap()
+}
+
+fn leaked_database<S: oprf::Suite, const L: usize>() -> Database<S, L> {
+    let mut rng = Rng::new(1);
+    let mut database = Database::new(Server::<S, Partial>::random(&mut rng));
+    database
+        .extend(LEAKED.map(|c| Input::try_from(c).unwrap()))
+        .unwrap();
+    database
+}
+
+#[test]
+fn leaked_credentials_are_found() {
+    let database = leaked_database::<Ristretto255, 1>();
+    assert_eq!(database.len(), LEAKED.len());
+    for credential in LEAKED {
+        assert!(check(&database, credential));
+    }
+
+    let database = leaked_database::<P256, 2>();
+    for credential in LEAKED {
+        assert!(check(&database, credential));
+    }
+}
+
+#[test]
+fn safe_credentials_are_not_found() {
+    let database = leaked_database::<Ristretto255, 1>();
+    assert!(!check(&database, b"alice:correct horse battery staple"));
+    assert!(!check(&database, b"erin:hunter2"));
+}
+
+#[test]
+fn wrong_key_is_rejected() {
+    let database = leaked_database::<Ristretto255, 1>();
+    let mut rng = Rng::new(3);
+    let other = Server::<Ristretto255, Partial>::random(&mut rng);
+
+    let credential = Input::try_from(LEAKED[0]).unwrap();
+    let bucket = Bucket::<1>::of::<Ristretto255>(credential);
+    let (check, blinded_element) =
+        Check::<Ristretto255>::new(credential, &bucket, other.verifying_key(), &mut rng).unwrap();
+    let (evaluated_element, proof) = database
+        .evaluate(blinded_element, &bucket, &mut rng)
+        .unwrap();
+    assert!(
+        check
+            .finish(evaluated_element, proof, database.bucket(&bucket))
+            .is_err()
+    );
+}
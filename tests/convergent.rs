@@ -0,0 +1,60 @@
+use oprf::{
+    convergent::{ContentHash, ContentHasher, FileKey, KeyRequest},
+    mode::Verifiable,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+fn file_keys<S: oprf::Suite, const N: usize>(
+    server: &Server<S, Verifiable>,
+    content_hashes: &[ContentHash<S>; N],
+    seed: u64,
+) -> [FileKey<32>; N] {
+    let mut rng = Rng::new(seed);
+    let (request, blinded_elements) =
+        KeyRequest::new(content_hashes, server.verifying_key(), &mut rng).unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, &mut rng);
+    request.finish(evaluated_elements, proof).unwrap()
+}
+
+#[test]
+fn identical_files_share_keys() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let files = [
+        ContentHash::new(b"quarterly report"),
+        ContentHash::new(b"holiday pictures"),
+        ContentHash::new(b"quarterly report"),
+    ];
+
+    let first = file_keys(&server, &files, 2);
+    let second = file_keys(&server, &[files[1].clone()], 3);
+    assert_eq!(first[0], first[2]);
+    assert_ne!(first[0], first[1]);
+    assert_eq!(first[1], second[0]);
+
+    let other = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(4));
+    assert_ne!(first[0], file_keys(&other, &[files[0].clone()], 2)[0]);
+}
+
+#[test]
+fn incremental_content_hash() {
+    let mut hasher = ContentHasher::<P256>::new();
+    hasher.update(b"quarterly ");
+    hasher.update(b"report");
+    assert_eq!(hasher.finalize(), ContentHash::new(b"quarterly report"));
+}
+
+#[test]
+fn misbehaving_key_server_is_detected() {
+    let server = Server::<P256, Verifiable>::random(&mut Rng::new(1));
+    let other = Server::<P256, Verifiable>::random(&mut Rng::new(2));
+    let files = [ContentHash::<P256>::new(b"quarterly report")];
+
+    let mut rng = Rng::new(3);
+    let (request, blinded_elements) =
+        KeyRequest::new(&files, server.verifying_key(), &mut rng).unwrap();
+    let (evaluated_elements, proof) = other.evaluate(blinded_elements, &mut rng);
+    assert!(request.finish::<32>(evaluated_elements, proof).is_err());
+}
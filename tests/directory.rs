@@ -0,0 +1,201 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input, InvalidEncoding,
+    client::Client,
+    directory::{KeyDirectory, TrustError, TrustStore},
+    mode::{Base, Verifiable},
+    rotation::KeyManager,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn encoding() {
+    let mut rng = Rng::new(1);
+    let mut directory = KeyDirectory::<Ristretto255, Verifiable>::new();
+    let first = Server::random(&mut rng);
+    let second = Server::random(&mut rng);
+    let id = directory.insert(&first, 0, 100).unwrap();
+    directory.insert(&second, 90, 200).unwrap();
+    assert_eq!(
+        directory.get(&id).unwrap().verifying_key,
+        first.verifying_key()
+    );
+
+    let bytes = directory.to_bytes();
+    assert!(bytes.starts_with(b"OPRF-DIR\x01\x01"));
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Verifiable>::from_bytes(&bytes),
+        Ok(directory.clone())
+    );
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Base>::from_bytes(&bytes).err(),
+        Some(InvalidEncoding)
+    );
+    assert_eq!(
+        KeyDirectory::<P256, Verifiable>::from_bytes(&bytes).err(),
+        Some(InvalidEncoding)
+    );
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Verifiable>::from_bytes(&bytes[..bytes.len() - 1]).err(),
+        Some(InvalidEncoding)
+    );
+
+    // The key ID must match the verifying key.
+    let mut tampered = bytes.clone();
+    tampered[24] ^= 1;
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Verifiable>::from_bytes(&tampered).err(),
+        Some(InvalidEncoding)
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json() {
+    let mut rng = Rng::new(1);
+    let mut directory = KeyDirectory::<Ristretto255, Verifiable>::new();
+    directory.insert(&Server::random(&mut rng), 0, 100).unwrap();
+
+    let json = directory.to_json().unwrap();
+    assert!(json.starts_with(r#"{"keys":[{"id":""#));
+    assert!(json.ends_with(r#"],"mode":1,"suite":"ristretto255-SHA512","version":1}"#));
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Verifiable>::from_json(&json),
+        Ok(directory)
+    );
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Base>::from_json(&json).err(),
+        Some(InvalidEncoding)
+    );
+    assert_eq!(
+        KeyDirectory::<Ristretto255, Verifiable>::from_json(&json.replace("100", "0")).err(),
+        Some(InvalidEncoding)
+    );
+}
+
+#[test]
+fn pinning() {
+    let mut rng = Rng::new(1);
+    let mut manager = KeyManager::<Ristretto255, Verifiable>::new(0);
+    let first = manager.insert(Server::random(&mut rng), 0, 100).unwrap();
+    let second = manager.insert(Server::random(&mut rng), 90, 200).unwrap();
+
+    // The same keys always give the same directory.
+    assert_eq!(
+        manager.directory().to_bytes(),
+        manager.directory().to_bytes()
+    );
+    assert_eq!(manager.directory().digest(), manager.directory().digest());
+
+    let mut store = TrustStore::new();
+    store.update(&manager.directory(), 0).unwrap();
+    assert_eq!(store.current(50).unwrap().0, first);
+    assert_eq!(store.current(95).unwrap().0, second);
+    assert_eq!(store.verifying_key(&second, 50), None);
+
+    // The pinned key is used to blind, and verifies the evaluation.
+    let (id, verifying_key) = store.current(50).unwrap();
+    let server = manager.evaluation_key(&id, 50).unwrap();
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let (client, [blinded]) =
+        Client::<_, Ristretto255, Verifiable>::blind([input], verifying_key, &mut rng).unwrap();
+    let (evaluated, proof) = server.evaluate([blinded], &mut rng);
+    assert!(client.finalize(evaluated, proof).is_ok());
+
+    // Changing the window of a pinned key, or removing it before it expires, is detected.
+    let mut changed = KeyManager::<Ristretto255, Verifiable>::new(0);
+    changed
+        .insert(
+            Server::from_secret_key(
+                manager
+                    .evaluation_key(&first, 50)
+                    .unwrap()
+                    .secret_key()
+                    .clone(),
+            ),
+            0,
+            150,
+        )
+        .unwrap();
+    changed
+        .insert(
+            Server::from_secret_key(
+                manager
+                    .evaluation_key(&second, 95)
+                    .unwrap()
+                    .secret_key()
+                    .clone(),
+            ),
+            90,
+            200,
+        )
+        .unwrap();
+    let mut updated = store.clone();
+    assert_eq!(
+        updated.update(&changed.directory(), 50),
+        Err(TrustError::KeyChanged(first))
+    );
+    assert_eq!(updated.current(50).unwrap().0, first);
+
+    manager.prune(100);
+    assert_eq!(
+        updated.update(&manager.directory(), 50),
+        Err(TrustError::KeyChanged(first))
+    );
+    assert_eq!(updated.update(&manager.directory(), 100), Ok(()));
+    assert_eq!(updated.ids().collect::<Vec<_>>(), [&second]);
+}
+
+#[test]
+fn invalid_proof() {
+    let mut rng = Rng::new(1);
+    let mut directory = KeyDirectory::<Ristretto255, Verifiable>::new();
+    directory.insert(&Server::random(&mut rng), 0, 100).unwrap();
+
+    // Swap the proof for one of the same key in another mode.
+    let mut bytes = directory.to_bytes();
+    let proof_len = 2 * (2 + 32);
+    let server = Server::<Ristretto255, Base>::from_secret_key(
+        Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1))
+            .secret_key()
+            .clone(),
+    );
+    let mut base = KeyDirectory::<Ristretto255, Base>::new();
+    base.insert(&server, 0, 100).unwrap();
+    let base = base.to_bytes();
+    let len = bytes.len();
+    bytes[len - proof_len..].copy_from_slice(&base[len - proof_len..]);
+
+    let directory = KeyDirectory::<Ristretto255, Verifiable>::from_bytes(&bytes).unwrap();
+    let mut store = TrustStore::new();
+    assert_eq!(store.update(&directory, 0), Err(TrustError::InvalidProof));
+    assert_eq!(store.ids().count(), 0);
+}
+
+#[test]
+fn too_many_keys() {
+    let mut rng = Rng::new(1);
+    let mut manager = KeyManager::<Ristretto255, Verifiable>::new(0);
+    manager.insert(Server::random(&mut rng), 0, 100).unwrap();
+    manager.insert(Server::random(&mut rng), 90, 200).unwrap();
+    let digest = manager.directory().digest();
+
+    // A third key valid at the same time as the others, e.g. to tag a single client.
+    manager.insert(Server::random(&mut rng), 95, 300).unwrap();
+    assert_ne!(manager.directory().digest(), digest);
+    let mut store = TrustStore::new();
+    assert_eq!(
+        store.update(&manager.directory(), 0),
+        Err(TrustError::TooManyKeys)
+    );
+    assert_eq!(store.ids().count(), 0);
+
+    // Keys that already expired don't count.
+    assert_eq!(store.update(&manager.directory(), 100), Ok(()));
+    let mut store = TrustStore::new().with_max_valid_keys(3);
+    assert_eq!(store.update(&manager.directory(), 0), Ok(()));
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input, Suite, UnsupportedSuite,
+    client::Client,
+    dynamic::Registry,
+    mode::{Base, Partial, Verifiable},
+    possession::verify_possession,
+    server::Server,
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+/// The ristretto255 suite, separated for a single application.
+struct Application;
+
+impl Suite for Application {
+    const IDENTIFIER: &'static [u8] = Ristretto255::IDENTIFIER;
+    const DOMAIN: &'static [u8] = b"example.com/passwords/v1";
+
+    type Group = <Ristretto255 as Suite>::Group;
+
+    type Hash = <Ristretto255 as Suite>::Hash;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        Ristretto255::hash_to_group(hash, domain)
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        Ristretto255::hash_to_scalar(hash, domain)
+    }
+}
+
+/// A suite whose identifier ends like the tag of a domain.
+struct Suffixed;
+
+impl Suite for Suffixed {
+    const IDENTIFIER: &'static [u8] = b"ristretto255-SHA512-App-a";
+
+    type Group = <Ristretto255 as Suite>::Group;
+
+    type Hash = <Ristretto255 as Suite>::Hash;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        Ristretto255::hash_to_group(hash, domain)
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        Ristretto255::hash_to_scalar(hash, domain)
+    }
+}
+
+/// The ristretto255 suite, with the domain `"a"`.
+struct Short;
+
+impl Suite for Short {
+    const IDENTIFIER: &'static [u8] = Ristretto255::IDENTIFIER;
+    const DOMAIN: &'static [u8] = b"a";
+
+    type Group = <Ristretto255 as Suite>::Group;
+
+    type Hash = <Ristretto255 as Suite>::Hash;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        Ristretto255::hash_to_group(hash, domain)
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        Ristretto255::hash_to_scalar(hash, domain)
+    }
+}
+
+#[test]
+fn derived_keys_differ() {
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+    let rfc = Server::<Ristretto255, Verifiable>::new([7; 32], info).unwrap();
+    let application = Server::<Application, Verifiable>::new([7; 32], info).unwrap();
+    assert_ne!(rfc.verifying_key(), application.verifying_key());
+
+    // The domain is length prefixed, so it can't be confused with the end of the identifier.
+    let suffixed = Server::<Suffixed, Verifiable>::new([7; 32], info).unwrap();
+    let short = Server::<Short, Verifiable>::new([7; 32], info).unwrap();
+    assert_ne!(suffixed.verifying_key(), short.verifying_key());
+}
+
+#[test]
+fn outputs_differ() {
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let rfc = Server::<Ristretto255, Base>::random(&mut Rng::new(1));
+    let application = Server::<Application, Base>::from_secret_key(rfc.secret_key().clone());
+
+    let mut rng = Rng::new(2);
+    let (client, blinded_elements) =
+        Client::<_, Application, Base>::blind([input], &mut rng).unwrap();
+    let output = client.finalize(application.evaluate(blinded_elements));
+    assert_eq!(output, application.full_evaluate([input]).unwrap());
+    assert_ne!(output, rfc.full_evaluate([input]).unwrap());
+
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+    let rfc = Server::<Ristretto255, Partial>::from_secret_key(rfc.secret_key().clone());
+    let application = Server::<Application, Partial>::from_secret_key(rfc.secret_key().clone());
+    assert_ne!(
+        rfc.full_evaluate([input], info).unwrap(),
+        application.full_evaluate([input], info).unwrap()
+    );
+}
+
+#[test]
+fn proofs_are_separated() {
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let application = Server::<Application, Verifiable>::random(&mut Rng::new(1));
+
+    let mut rng = Rng::new(2);
+    let (client, blinded_elements) =
+        Client::<_, Application, Verifiable>::blind([input], application.verifying_key(), &mut rng)
+            .unwrap();
+    let (evaluated_elements, proof) = application.evaluate(blinded_elements, &mut rng);
+    assert!(client.finalize(evaluated_elements, proof).is_ok());
+
+    // The same key and evaluation, verified in the domain of RFC 9497.
+    let (client, _) = Client::<_, Ristretto255, Verifiable>::blind(
+        [input],
+        application.verifying_key(),
+        &mut Rng::new(2),
+    )
+    .unwrap();
+    assert!(client.finalize(evaluated_elements, proof).is_err());
+
+    let proof = application.prove_possession(input, &mut rng);
+    assert!(
+        verify_possession::<Application, Verifiable>(application.verifying_key(), input, proof)
+            .is_ok()
+    );
+    assert!(
+        verify_possession::<Ristretto255, Verifiable>(application.verifying_key(), input, proof)
+            .is_err()
+    );
+}
+
+#[test]
+fn registry_rejects_domains() {
+    assert_eq!(
+        Registry::new().register::<Application>(),
+        Err(UnsupportedSuite)
+    );
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "alloc")]
+
+use group::{Group, GroupEncoding};
+use oprf::{
+    Input, InvalidEncoding, Suite,
+    client::Client,
+    dynamic::{AnyMode, AnySuite, DynError, Registry},
+    mode::{Base, Partial, Verifiable},
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+fn registry() -> Registry {
+    let mut registry = Registry::new();
+    assert_eq!(
+        registry.register::<Ristretto255>(),
+        Ok(AnySuite::Ristretto255Sha512)
+    );
+    assert_eq!(registry.register::<P256>(), Ok(AnySuite::P256Sha256));
+    registry
+}
+
+#[test]
+fn identifiers() {
+    for suite in AnySuite::ALL {
+        assert_eq!(AnySuite::from_identifier(suite.identifier()), Some(suite));
+    }
+    assert_eq!(
+        AnySuite::from_identifier(b"P256-SHA256"),
+        Some(AnySuite::P256Sha256)
+    );
+    assert_eq!(AnySuite::from_identifier(b"P256"), None);
+    assert_eq!(AnyMode::from_identifier(0x02), Some(AnyMode::Partial));
+    assert_eq!(AnyMode::from_identifier(0x03), None);
+}
+
+#[test]
+fn same_as_static() {
+    let registry = registry();
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+
+    // Base
+    let server = Server::<Ristretto255, Base>::random(&mut Rng::new(1));
+    let secret_key = server.secret_key().expose_secret().to_bytes();
+    let (client, [blinded]) =
+        Client::<_, Ristretto255, Base>::blind([input], &mut Rng::new(2)).unwrap();
+    let [expected] = client.finalize(server.evaluate([blinded]));
+
+    let dyn_server = registry
+        .server(AnySuite::Ristretto255Sha512, AnyMode::Base, &secret_key)
+        .unwrap();
+    let (dyn_client, dyn_blinded) = registry
+        .blind(
+            AnySuite::Ristretto255Sha512,
+            AnyMode::Base,
+            input,
+            None,
+            None,
+            &mut Rng::new(2),
+        )
+        .unwrap();
+    assert_eq!(dyn_blinded, blinded.0.to_bytes().as_slice());
+    let evaluation = dyn_server
+        .evaluate(&dyn_blinded, None, &mut Rng::new(3))
+        .unwrap();
+    assert_eq!(
+        dyn_client.finalize(&evaluation).unwrap(),
+        expected.as_slice()
+    );
+
+    // Verifiable
+    let server = Server::<P256, Verifiable>::random(&mut Rng::new(1));
+    let (client, [blinded]) =
+        Client::<_, P256, Verifiable>::blind([input], server.verifying_key(), &mut Rng::new(2))
+            .unwrap();
+    let (evaluated, proof) = server.evaluate([blinded], &mut Rng::new(3));
+    let [expected] = client.finalize(evaluated, proof).unwrap();
+
+    let dyn_server = registry
+        .random_server(AnySuite::P256Sha256, AnyMode::Verifiable, &mut Rng::new(1))
+        .unwrap();
+    assert_eq!(
+        dyn_server.verifying_key(),
+        server.verifying_key().0.to_bytes().as_slice()
+    );
+    let (dyn_client, dyn_blinded) = registry
+        .blind(
+            AnySuite::P256Sha256,
+            AnyMode::Verifiable,
+            input,
+            None,
+            Some(&dyn_server.verifying_key()),
+            &mut Rng::new(2),
+        )
+        .unwrap();
+    let evaluation = dyn_server
+        .evaluate(&dyn_blinded, None, &mut Rng::new(3))
+        .unwrap();
+    assert_eq!(
+        dyn_client.finalize(&evaluation).unwrap(),
+        expected.as_slice()
+    );
+
+    // Partial
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let (client, [blinded]) = Client::<_, Ristretto255, Partial>::blind(
+        [input],
+        info,
+        server.verifying_key(),
+        &mut Rng::new(2),
+    )
+    .unwrap();
+    let (evaluated, proof) = server.evaluate([blinded], info, &mut Rng::new(3)).unwrap();
+    let [expected] = client.finalize(evaluated, proof).unwrap();
+
+    let dyn_server = registry
+        .random_server(
+            AnySuite::Ristretto255Sha512,
+            AnyMode::Partial,
+            &mut Rng::new(1),
+        )
+        .unwrap();
+    let (dyn_client, dyn_blinded) = registry
+        .blind(
+            AnySuite::Ristretto255Sha512,
+            AnyMode::Partial,
+            input,
+            Some(info),
+            Some(&dyn_server.verifying_key()),
+            &mut Rng::new(2),
+        )
+        .unwrap();
+    let evaluation = dyn_server
+        .evaluate(&dyn_blinded, Some(info), &mut Rng::new(3))
+        .unwrap();
+    assert_eq!(
+        dyn_client.finalize(&evaluation).unwrap(),
+        expected.as_slice()
+    );
+}
+
+#[test]
+fn errors() {
+    let registry = registry();
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let mut rng = Rng::new(1);
+
+    assert_eq!(
+        registry
+            .random_server(AnySuite::P384Sha384, AnyMode::Base, &mut rng)
+            .err(),
+        Some(DynError::UnknownSuite)
+    );
+    assert_eq!(
+        registry
+            .server(AnySuite::P256Sha256, AnyMode::Base, &[0; 32])
+            .err(),
+        Some(DynError::InvalidEncoding(InvalidEncoding))
+    );
+
+    let server = registry
+        .random_server(AnySuite::P256Sha256, AnyMode::Verifiable, &mut rng)
+        .unwrap();
+    // The verifying key is required in the verifiable mode.
+    assert_eq!(
+        registry
+            .blind(
+                AnySuite::P256Sha256,
+                AnyMode::Verifiable,
+                input,
+                None,
+                None,
+                &mut rng
+            )
+            .err(),
+        Some(DynError::InvalidParameters)
+    );
+
+    // A proof from another key is rejected.
+    let other = registry
+        .random_server(AnySuite::P256Sha256, AnyMode::Verifiable, &mut rng)
+        .unwrap();
+    let (client, blinded) = registry
+        .blind(
+            AnySuite::P256Sha256,
+            AnyMode::Verifiable,
+            input,
+            None,
+            Some(&server.verifying_key()),
+            &mut rng,
+        )
+        .unwrap();
+    let evaluation = other.evaluate(&blinded, None, &mut rng).unwrap();
+    assert!(matches!(
+        client.finalize(&evaluation),
+        Err(DynError::InvalidProof(_))
+    ));
+    assert_eq!(
+        server.evaluate(&blinded[1..], None, &mut rng),
+        Err(DynError::InvalidEncoding(InvalidEncoding))
+    );
+
+    // An evaluated element equal to the identity is rejected, even without a proof.
+    let (client, _) = registry
+        .blind(
+            AnySuite::Ristretto255Sha512,
+            AnyMode::Base,
+            input,
+            None,
+            None,
+            &mut rng,
+        )
+        .unwrap();
+    let identity = <Ristretto255 as Suite>::Group::identity().to_bytes();
+    assert_eq!(
+        client.finalize(identity.as_ref()),
+        Err(DynError::InvalidEncoding(InvalidEncoding))
+    );
+}
@@ -0,0 +1,118 @@
+use group::{Group, ff::Field};
+use oprf::{
+    Input, Suite,
+    hardening::{HardeningServer, InvalidToken, PasswordRecord, Query},
+    mode::Partial,
+    primitives,
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+type Record = PasswordRecord<curve25519_dalek::RistrettoPoint>;
+type Scalar = <<Ristretto255 as Suite>::Group as Group>::Scalar;
+
+fn enroll(server: &HardeningServer<Ristretto255>, password: &[u8], tweak: &[u8]) -> Record {
+    let mut rng = Rng::new(password.len() as u64);
+    let password = Input::try_from(password).unwrap();
+    let tweak = Input::try_from(tweak).unwrap();
+    let verifying_key = server.verifying_key(tweak).unwrap();
+    let (query, blinded_element) =
+        Query::<Ristretto255>::new(password, verifying_key, &mut rng).unwrap();
+    let (evaluated_element, proof) = server.evaluate(blinded_element, tweak, &mut rng).unwrap();
+    query.enroll(evaluated_element, proof).unwrap()
+}
+
+fn verify(
+    server: &HardeningServer<Ristretto255>,
+    password: &[u8],
+    tweak: &[u8],
+    record: &Record,
+) -> bool {
+    let mut rng = Rng::new(42);
+    let password = Input::try_from(password).unwrap();
+    let tweak = Input::try_from(tweak).unwrap();
+    let (query, blinded_element) =
+        Query::<Ristretto255>::new(password, record.verifying_key, &mut rng).unwrap();
+    let (evaluated_element, proof) = server.evaluate(blinded_element, tweak, &mut rng).unwrap();
+    query
+        .verify(evaluated_element, proof, record)
+        .unwrap_or(false)
+}
+
+#[test]
+fn enroll_and_verify() {
+    let server = HardeningServer::new([1; 32]);
+    let alice = enroll(&server, b"hunter2", b"tenant-a/alice");
+    let bob = enroll(&server, b"hunter2", b"tenant-a/bob");
+    assert_ne!(alice, bob);
+    assert_ne!(alice.verifying_key, bob.verifying_key);
+
+    assert!(verify(&server, b"hunter2", b"tenant-a/alice", &alice));
+    assert!(!verify(&server, b"hunter3", b"tenant-a/alice", &alice));
+    assert!(!verify(&server, b"hunter2", b"tenant-a/bob", &alice));
+}
+
+#[test]
+fn rotation() {
+    let mut server = HardeningServer::new([1; 32]);
+    let mut alice = enroll(&server, b"hunter2", b"alice");
+    let mut bob = enroll(&server, b"correct horse", b"bob");
+
+    let rotation = server.rotate([2; 32]);
+    let alice_token = rotation
+        .update_token(Input::try_from(b"alice".as_slice()).unwrap())
+        .unwrap();
+    let bob_token = rotation
+        .update_token(Input::try_from(b"bob".as_slice()).unwrap())
+        .unwrap();
+    drop(rotation);
+
+    assert!(!verify(&server, b"hunter2", b"alice", &alice));
+
+    assert_eq!(bob.update(&alice_token), Err(InvalidToken));
+    alice.update(&alice_token).unwrap();
+    bob.update(&bob_token).unwrap();
+    let alice_tweak = Input::try_from(b"alice".as_slice()).unwrap();
+    assert_eq!(
+        alice.verifying_key,
+        server.verifying_key(alice_tweak).unwrap()
+    );
+
+    assert!(verify(&server, b"hunter2", b"alice", &alice));
+    assert!(verify(&server, b"correct horse", b"bob", &bob));
+    assert!(!verify(&server, b"hunter2", b"bob", &bob));
+    assert_eq!(alice, enroll(&server, b"hunter2", b"alice"));
+}
+
+/// The tokens of two tweaks don't reveal the keys, as they would if the keys of the tweaks were
+/// tweaks `k + m` of a shared key.
+#[test]
+fn tokens_do_not_reveal_keys() {
+    let mut server = HardeningServer::<Ristretto255>::new([1; 32]);
+    let tweaks = [b"alice".as_slice(), b"bob".as_slice()].map(|t| Input::try_from(t).unwrap());
+    let previous = tweaks.map(|t| server.verifying_key(t).unwrap());
+    let rotation = server.rotate([2; 32]);
+    let [d1, d2] = tweaks.map(|t| rotation.update_token(t).unwrap().delta);
+    let [m1, m2] = tweaks.map(|t| {
+        primitives::hash_to_scalar::<Ristretto255, Partial>(&[
+            b"Info",
+            &(t.as_ref().len() as u16).to_be_bytes(),
+            t.as_ref(),
+        ])
+    });
+
+    // Solve `k = d * (k' + m) - m` for both tweaks, with `d = (k + m) / (k' + m)`.
+    let current =
+        (m1 * (Scalar::ONE - d1) - m2 * (Scalar::ONE - d2)) * Field::invert(&(d1 - d2)).unwrap();
+    let candidates = [current, d1 * (current + m1) - m1, d2 * (current + m2) - m2];
+    for candidate in candidates {
+        let candidate = <Ristretto255 as Suite>::Group::mul_by_generator(&candidate);
+        for tweak in tweaks {
+            assert_ne!(candidate, server.verifying_key(tweak).unwrap().0);
+        }
+        for key in previous {
+            assert_ne!(candidate, key.0);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "jwk")]
+
+use oprf::{InvalidEncoding, jwk, mode::Verifiable, server::Server};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn round_trip() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+
+    let json = jwk::to_jwk::<Ristretto255>(server.secret_key()).unwrap();
+    assert!(json.starts_with(r#"{"kty":"OPRF","suite":"ristretto255-SHA512","x":""#));
+    let key = jwk::from_jwk::<Ristretto255>(&json).unwrap();
+    assert_eq!(&key, server.secret_key());
+    assert_eq!(
+        jwk::verifying_key_from_jwk::<Ristretto255>(&json),
+        Ok(server.verifying_key())
+    );
+
+    let json = jwk::verifying_key_to_jwk::<Ristretto255>(server.verifying_key()).unwrap();
+    assert!(!json.contains(r#""d""#));
+    assert_eq!(
+        jwk::verifying_key_from_jwk::<Ristretto255>(&json),
+        Ok(server.verifying_key())
+    );
+    assert_eq!(jwk::from_jwk::<Ristretto255>(&json), Err(InvalidEncoding));
+}
+
+#[test]
+fn reject() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(2));
+    let json = jwk::to_jwk::<Ristretto255>(server.secret_key()).unwrap();
+
+    // The suite must match.
+    assert_eq!(jwk::from_jwk::<P256>(&json).err(), Some(InvalidEncoding));
+    assert_eq!(
+        jwk::verifying_key_from_jwk::<P256>(&json).err(),
+        Some(InvalidEncoding)
+    );
+    assert_eq!(
+        jwk::from_jwk::<Ristretto255>(&json.replace(r#""kty":"OPRF""#, r#""kty":"EC""#)),
+        Err(InvalidEncoding)
+    );
+
+    // The verifying key must match the secret key.
+    let other = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(3));
+    let other = jwk::to_jwk::<Ristretto255>(other.secret_key()).unwrap();
+    let x = |json: &str| {
+        json.split(r#""x":"#)
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .next()
+            .unwrap()
+            .to_owned()
+    };
+    let json = json.replace(&x(&json), &x(&other));
+    assert_eq!(jwk::from_jwk::<Ristretto255>(&json), Err(InvalidEncoding));
+
+    assert_eq!(
+        jwk::from_jwk::<Ristretto255>(r#"{"kty":"OPRF"}"#),
+        Err(InvalidEncoding)
+    );
+    assert_eq!(jwk::from_jwk::<Ristretto255>("[]"), Err(InvalidEncoding));
+}
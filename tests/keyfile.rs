@@ -0,0 +1,118 @@
+#![cfg(feature = "keyfile")]
+
+use oprf::{
+    keyfile::{self, InvalidKdfParams, KdfParams, KeyFileError},
+    mode::{Base, Partial, Verifiable},
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+const PARAMS: KdfParams = KdfParams {
+    memory: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+#[test]
+fn round_trip() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let file = keyfile::encrypt(&server, b"passphrase", PARAMS, &mut Rng::new(2)).unwrap();
+    assert!(file.starts_with(b"OPRF-KEY\x01\x01"));
+
+    let decrypted =
+        keyfile::decrypt::<Ristretto255, Verifiable>(&file, b"passphrase", PARAMS).unwrap();
+    assert_eq!(decrypted.secret_key(), server.secret_key());
+    assert_eq!(decrypted.verifying_key(), server.verifying_key());
+
+    let server = Server::<P256, Partial>::random(&mut Rng::new(3));
+    let file = keyfile::encrypt(&server, b"", PARAMS, &mut Rng::new(4)).unwrap();
+    let decrypted = keyfile::decrypt::<P256, Partial>(&file, b"", PARAMS).unwrap();
+    assert_eq!(decrypted.secret_key(), server.secret_key());
+}
+
+#[test]
+fn reject() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let file = keyfile::encrypt(&server, b"passphrase", PARAMS, &mut Rng::new(2)).unwrap();
+
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(&file, b"wrong", PARAMS).err(),
+        Some(KeyFileError::DecryptionFailed)
+    );
+    assert_eq!(
+        keyfile::decrypt::<P256, Verifiable>(&file, b"passphrase", PARAMS).err(),
+        Some(KeyFileError::SuiteMismatch)
+    );
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Base>(&file, b"passphrase", PARAMS).err(),
+        Some(KeyFileError::ModeMismatch)
+    );
+
+    // The header is authenticated.
+    let mut tampered = file.clone();
+    let salt = tampered.len() - 16 - 32 - 12 - 16;
+    tampered[salt] ^= 1;
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(&tampered, b"passphrase", PARAMS).err(),
+        Some(KeyFileError::DecryptionFailed)
+    );
+
+    let mut tampered = file.clone();
+    tampered[8] = 2;
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(&tampered, b"passphrase", PARAMS).err(),
+        Some(KeyFileError::UnsupportedVersion)
+    );
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(
+            &file[..file.len() - 1],
+            b"passphrase",
+            PARAMS
+        )
+        .err(),
+        Some(KeyFileError::InvalidEncoding)
+    );
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(b"OPRF-KEY", b"passphrase", PARAMS).err(),
+        Some(KeyFileError::InvalidEncoding)
+    );
+
+    // The parameters are bounded before deriving the key.
+    let file = keyfile::encrypt(
+        &server,
+        b"passphrase",
+        KdfParams {
+            memory: 128,
+            ..PARAMS
+        },
+        &mut Rng::new(2),
+    )
+    .unwrap();
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(&file, b"passphrase", PARAMS).err(),
+        Some(KeyFileError::ExcessiveKdfParams)
+    );
+    let mut tampered = file.clone();
+    let iterations = 8 + 1 + 1 + 2 + 19 + 4;
+    tampered[iterations..iterations + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        keyfile::decrypt::<Ristretto255, Verifiable>(
+            &tampered,
+            b"passphrase",
+            KdfParams::default()
+        )
+        .err(),
+        Some(KeyFileError::ExcessiveKdfParams)
+    );
+
+    let params = KdfParams {
+        parallelism: 0,
+        ..PARAMS
+    };
+    assert_eq!(
+        keyfile::encrypt(&server, b"passphrase", params, &mut Rng::new(2)),
+        Err(InvalidKdfParams)
+    );
+}
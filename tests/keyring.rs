@@ -0,0 +1,59 @@
+#![cfg(feature = "alloc")]
+
+use std::num::NonZeroUsize;
+
+use oprf::{Input, keyring::KeyRing, mode::Verifiable, server::Server};
+use suite::Ristretto255;
+
+mod suite;
+
+const SEED: [u8; 32] = [7; 32];
+
+fn tenant(id: &[u8]) -> Input<'_> {
+    Input::try_from(id).unwrap()
+}
+
+#[test]
+fn derive() {
+    let mut keyring = KeyRing::<Ristretto255, Verifiable>::new(SEED, NonZeroUsize::new(2).unwrap());
+    assert!(keyring.is_empty());
+
+    let alice = keyring.verifying_key(tenant(b"alice")).unwrap();
+    let bob = keyring.verifying_key(tenant(b"bob")).unwrap();
+    assert_ne!(alice, bob);
+    assert_eq!(
+        alice,
+        Server::<Ristretto255, Verifiable>::new(SEED, tenant(b"alice"))
+            .unwrap()
+            .verifying_key()
+    );
+    assert_eq!(
+        KeyRing::<Ristretto255, Verifiable>::new(SEED, NonZeroUsize::new(1).unwrap())
+            .verifying_key(tenant(b"bob"))
+            .unwrap(),
+        bob
+    );
+    assert_eq!(keyring.len(), 2);
+}
+
+#[test]
+fn eviction() {
+    let mut keyring = KeyRing::<Ristretto255, Verifiable>::new(SEED, NonZeroUsize::new(2).unwrap());
+    let alice = keyring.verifying_key(tenant(b"alice")).unwrap();
+    keyring.server(tenant(b"bob")).unwrap();
+    keyring.server(tenant(b"alice")).unwrap();
+
+    // Bob is the least recently used.
+    keyring.server(tenant(b"carol")).unwrap();
+    assert_eq!(keyring.len(), 2);
+    assert!(keyring.contains(tenant(b"alice")));
+    assert!(!keyring.contains(tenant(b"bob")));
+    assert!(keyring.contains(tenant(b"carol")));
+
+    keyring.evict(tenant(b"alice"));
+    assert!(!keyring.contains(tenant(b"alice")));
+    assert_eq!(keyring.verifying_key(tenant(b"alice")).unwrap(), alice);
+
+    keyring.clear();
+    assert!(keyring.is_empty());
+}
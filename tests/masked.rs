@@ -0,0 +1,104 @@
+use oprf::{
+    Input,
+    client::Client,
+    masked::MaskedServer,
+    mode::{Base, Partial, Verifiable},
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn base() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Base>::random(&mut rng);
+    let mut masked = MaskedServer::<Ristretto255, Base>::new(
+        Server::from_secret_key(server.secret_key().clone()),
+        &mut rng,
+    );
+    assert_eq!(masked.verifying_key(), server.verifying_key());
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+
+    let (client, blinded_elements) =
+        Client::<_, Ristretto255, Base>::blind(inputs, &mut rng).unwrap();
+    for _ in 0..3 {
+        let evaluated_elements = masked.evaluate(blinded_elements, &mut rng).unwrap();
+        assert_eq!(evaluated_elements, server.evaluate(blinded_elements));
+    }
+    let evaluated_elements = masked.evaluate(blinded_elements, &mut rng).unwrap();
+    assert_eq!(
+        client.finalize(evaluated_elements),
+        server.full_evaluate(inputs).unwrap()
+    );
+}
+
+#[test]
+fn verifiable() {
+    let mut rng = Rng::new(1);
+    let server = Server::<P256, Verifiable>::random(&mut rng);
+    let mut masked = MaskedServer::<P256, Verifiable>::new(
+        Server::from_secret_key(server.secret_key().clone()),
+        &mut rng,
+    );
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+
+    let (client, blinded_elements) =
+        Client::<_, P256, Verifiable>::blind(inputs, masked.verifying_key(), &mut rng).unwrap();
+    let (evaluated_elements, proof) = masked.evaluate(blinded_elements, &mut rng).unwrap();
+    assert_eq!(
+        evaluated_elements,
+        server.evaluate(blinded_elements, &mut rng).0
+    );
+    assert_eq!(
+        client.finalize(evaluated_elements, proof).unwrap(),
+        server.full_evaluate(inputs).unwrap()
+    );
+}
+
+#[test]
+fn partial() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Partial>::random(&mut rng);
+    let mut masked = MaskedServer::<Ristretto255, Partial>::new(
+        Server::from_secret_key(server.secret_key().clone()),
+        &mut rng,
+    );
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+
+    let (client, blinded_elements) =
+        Client::<_, Ristretto255, Partial>::blind(inputs, info, masked.verifying_key(), &mut rng)
+            .unwrap();
+    let (evaluated_elements, proof) = masked.evaluate(blinded_elements, info, &mut rng).unwrap();
+    assert_eq!(
+        evaluated_elements,
+        server.evaluate(blinded_elements, info, &mut rng).unwrap().0
+    );
+    assert_eq!(
+        client.finalize(evaluated_elements, proof).unwrap(),
+        server.full_evaluate(inputs, info).unwrap()
+    );
+}
+
+#[test]
+fn repeated_randomness() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let mut masked = MaskedServer::<Ristretto255, Verifiable>::new(server, &mut rng);
+    let inputs = [Input::try_from(b"alpha".as_slice()).unwrap()];
+
+    // The same randomness must not lead to the same nonce for different evaluations.
+    let (_, blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind(inputs, masked.verifying_key(), &mut rng)
+            .unwrap();
+    let (client, other_blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind(inputs, masked.verifying_key(), &mut rng)
+            .unwrap();
+    let (_, first) = masked.evaluate(blinded_elements, &mut Rng::new(7)).unwrap();
+    let (evaluated_elements, second) = masked
+        .evaluate(other_blinded_elements, &mut Rng::new(7))
+        .unwrap();
+    assert_ne!(first.s, second.s);
+    client.finalize(evaluated_elements, second).unwrap();
+}
@@ -0,0 +1,145 @@
+use group::{Group, ff::Field};
+use oprf::{
+    Input, Suite, VerifyingKey,
+    mode::{Base, Empty, Identifier, Mode, ServerPayload, Verifiable},
+    possession::verify_possession,
+    primitives,
+    server::Server,
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+/// A mode defined outside of the crate, with its own server payload.
+struct Custom;
+
+impl Mode for Custom {
+    const IDENTIFIER: Identifier<Self> = Identifier::custom(0x80);
+
+    type ServerPayload<S: Suite> = CustomPayload<S::Group>;
+}
+
+struct CustomPayload<G>(VerifyingKey<G>);
+
+impl<S: Suite> ServerPayload<S> for CustomPayload<S::Group> {
+    fn from_verifying_key(verifying_key: VerifyingKey<S::Group>) -> Self {
+        CustomPayload(verifying_key)
+    }
+
+    fn verifying_key(&self) -> Option<VerifyingKey<S::Group>> {
+        Some(self.0)
+    }
+}
+
+/// A mode without payload.
+struct Plain;
+
+impl Mode for Plain {
+    const IDENTIFIER: Identifier<Self> = Identifier::custom(0x81);
+
+    type ServerPayload<S: Suite> = Empty;
+}
+
+#[test]
+fn custom_mode() {
+    let info = Input::try_from(b"info".as_slice()).unwrap();
+    let seed = [3; 32];
+
+    // The identifier of the mode separates the derived keys.
+    let base = Server::<Ristretto255, Base>::new(seed, info).unwrap();
+    let custom = Server::<Ristretto255, Custom>::new(seed, info).unwrap();
+    let plain = Server::<Ristretto255, Plain>::new(seed, info).unwrap();
+    assert_ne!(custom.verifying_key(), base.verifying_key());
+    assert_ne!(custom.verifying_key(), plain.verifying_key());
+
+    let server = Server::<Ristretto255, Custom>::random(&mut Rng::new(1));
+    let verifying_key = server.verifying_key();
+    assert_eq!(
+        verifying_key.0,
+        <Ristretto255 as Suite>::Group::mul_by_generator(server.secret_key().expose_secret())
+    );
+
+    // The proof of possession is bound to the custom mode.
+    let proof = server.static_possession_proof();
+    let context = Input::try_from(b"".as_slice()).unwrap();
+    assert!(verify_possession::<Ristretto255, Custom>(verifying_key, context, proof).is_ok());
+    assert!(verify_possession::<Ristretto255, Plain>(verifying_key, context, proof).is_err());
+}
+
+#[test]
+fn custom_protocol() {
+    type Scalar = <<Ristretto255 as Suite>::Group as Group>::Scalar;
+
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let mut rng = Rng::new(2);
+    let server = Server::<Ristretto255, Custom>::random(&mut Rng::new(1));
+    let k = *server.secret_key().expose_secret();
+
+    // The client blinds its input, hashed in the domain of the custom mode.
+    let element = primitives::hash_to_group::<Ristretto255, Custom>(input).unwrap();
+    let blind = Scalar::random(&mut rng);
+    let blinded = [element * blind];
+
+    // The server evaluates it, and proves the evaluation.
+    let evaluated = [blinded[0] * k];
+    let proof = primitives::prove(&server, &blinded, &evaluated, &mut rng);
+
+    // The proof is bound to the custom mode.
+    let verifying_key = server.verifying_key();
+    assert!(
+        primitives::verify::<Ristretto255, Custom>(verifying_key, &blinded, &evaluated, proof)
+            .is_ok()
+    );
+    assert!(
+        primitives::verify::<Ristretto255, Plain>(verifying_key, &blinded, &evaluated, proof)
+            .is_err()
+    );
+    assert!(
+        primitives::verify::<Ristretto255, Verifiable>(verifying_key, &blinded, &evaluated, proof)
+            .is_err()
+    );
+    assert!(
+        primitives::verify::<Ristretto255, Custom>(verifying_key, &blinded, &blinded, proof)
+            .is_err()
+    );
+
+    let output = primitives::finalize::<Ristretto255>(
+        input,
+        None,
+        evaluated[0] * Field::invert(&blind).unwrap(),
+    );
+    assert_eq!(
+        output,
+        primitives::finalize::<Ristretto255>(input, None, element * k)
+    );
+
+    // The same key in the verifiable mode of RFC 9497 hashes the input in another domain.
+    let rfc = Server::<Ristretto255, Verifiable>::from_secret_key(server.secret_key().clone());
+    assert_ne!(output, rfc.full_evaluate([input]).unwrap()[0]);
+    assert_ne!(
+        primitives::hash_to_scalar::<Ristretto255, Custom>(&[b"input"]),
+        primitives::hash_to_scalar::<Ristretto255, Verifiable>(&[b"input"])
+    );
+}
+
+#[test]
+fn too_many_elements() {
+    let server = Server::<Ristretto255, Custom>::random(&mut Rng::new(1));
+    let input = Input::try_from(b"input".as_slice()).unwrap();
+    let element = primitives::hash_to_group::<Ristretto255, Custom>(input).unwrap();
+    let k = *server.secret_key().expose_secret();
+    let proof = primitives::prove(&server, &[element], &[element * k], &mut Rng::new(2));
+
+    // The index of an element is encoded on 2 bytes, so it would wrap around after 2^16 elements.
+    let blinded = vec![element; (1 << 16) + 1];
+    let evaluated = vec![element * k; (1 << 16) + 1];
+    assert!(
+        primitives::verify::<Ristretto255, Custom>(
+            server.verifying_key(),
+            &blinded,
+            &evaluated,
+            proof
+        )
+        .is_err()
+    );
+}
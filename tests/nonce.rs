@@ -0,0 +1,66 @@
+use oprf::{
+    Input,
+    client::Client,
+    mode::{Partial, Verifiable},
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn verifiable() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+
+    let (client, blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind(inputs, server.verifying_key(), &mut rng)
+            .unwrap();
+    let (evaluated_elements, proof) = server.evaluate_deterministic(blinded_elements);
+    assert_eq!(
+        server.evaluate_deterministic(blinded_elements),
+        (evaluated_elements, proof)
+    );
+    client.finalize(evaluated_elements, proof).unwrap();
+
+    // The same randomness must not lead to the same nonce for different evaluations.
+    let (client, other_blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind(inputs, server.verifying_key(), &mut rng)
+            .unwrap();
+    let (_, first) = server.evaluate_hedged(blinded_elements, &mut Rng::new(7));
+    let (evaluated_elements, second) =
+        server.evaluate_hedged(other_blinded_elements, &mut Rng::new(7));
+    assert_ne!(first.s, second.s);
+    client.finalize(evaluated_elements, second).unwrap();
+}
+
+#[test]
+fn partial() {
+    let mut rng = Rng::new(1);
+    let server = Server::<P256, Partial>::random(&mut rng);
+    let inputs = [Input::try_from(b"alpha".as_slice()).unwrap()];
+
+    for info in [b"first".as_slice(), b"second"] {
+        let info = Input::try_from(info).unwrap();
+        let (client, blinded_elements) =
+            Client::<_, P256, Partial>::blind(inputs, info, server.verifying_key(), &mut rng)
+                .unwrap();
+        let (evaluated_elements, proof) = server
+            .evaluate_deterministic(blinded_elements, info)
+            .unwrap();
+        assert_eq!(
+            server.evaluate_deterministic(blinded_elements, info),
+            Ok((evaluated_elements, proof))
+        );
+        client.finalize(evaluated_elements, proof).unwrap();
+
+        let (client, blinded_elements) =
+            Client::<_, P256, Partial>::blind(inputs, info, server.verifying_key(), &mut rng)
+                .unwrap();
+        let (evaluated_elements, proof) = server
+            .evaluate_hedged(blinded_elements, info, &mut rng)
+            .unwrap();
+        client.finalize(evaluated_elements, proof).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 use digest::consts::U48;
-use group::ff::PrimeField;
+use group::{Curve, GroupEncoding, ff::PrimeField};
 use oprf::mode::{Base, Partial, Verifiable};
-use p256::ProjectivePoint;
+use p256::{AffinePoint, ProjectivePoint};
 use sha2::Sha256;
 use vector::parse_vectors;
 
@@ -29,6 +29,14 @@ impl oprf::Suite for P256 {
         )
         .expect("properly constructed")
     }
+
+    fn batch_to_bytes(elements: &[Self::Group], out: &mut [<Self::Group as GroupEncoding>::Repr]) {
+        let mut affine = vec![AffinePoint::IDENTITY; elements.len()];
+        ProjectivePoint::batch_normalize(elements, &mut affine);
+        for (affine, out) in affine.iter().zip(out) {
+            *out = affine.to_bytes();
+        }
+    }
 }
 
 #[test]
@@ -0,0 +1,103 @@
+use oprf::{
+    Input,
+    client::InvalidProof,
+    mode::{Base, Partial, Verifiable},
+    possession::verify_possession,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+const CONTEXT: &[u8] = b"client nonce";
+
+#[test]
+fn prove_and_verify() {
+    let context = Input::try_from(CONTEXT).unwrap();
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let proof = server.prove_possession(context, &mut Rng::new(2));
+    assert_eq!(
+        verify_possession::<Ristretto255, Verifiable>(server.verifying_key(), context, proof),
+        Ok(())
+    );
+
+    let server = Server::<P256, Base>::random(&mut Rng::new(3));
+    let proof = server.prove_possession(context, &mut Rng::new(4));
+    assert_eq!(
+        verify_possession::<P256, Base>(server.verifying_key(), context, proof),
+        Ok(())
+    );
+}
+
+#[test]
+fn static_proof() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let proof = server.static_possession_proof();
+    assert_eq!(server.static_possession_proof(), proof);
+    assert_eq!(
+        verify_possession::<Ristretto255, Verifiable>(
+            server.verifying_key(),
+            Input::try_from(b"".as_slice()).unwrap(),
+            proof
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        verify_possession::<Ristretto255, Verifiable>(
+            server.verifying_key(),
+            Input::try_from(CONTEXT).unwrap(),
+            proof
+        ),
+        Err(InvalidProof)
+    );
+}
+
+#[test]
+fn reject() {
+    let context = Input::try_from(CONTEXT).unwrap();
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let proof = server.prove_possession(context, &mut Rng::new(2));
+
+    // The proof is bound to the key.
+    let other = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(3));
+    assert_eq!(
+        verify_possession::<Ristretto255, Verifiable>(other.verifying_key(), context, proof),
+        Err(InvalidProof)
+    );
+
+    // The proof is bound to the context, so it can't be replayed for another challenge.
+    assert_eq!(
+        verify_possession::<Ristretto255, Verifiable>(
+            server.verifying_key(),
+            Input::try_from(b"another nonce".as_slice()).unwrap(),
+            proof
+        ),
+        Err(InvalidProof)
+    );
+
+    // The proof is bound to the mode.
+    assert_eq!(
+        verify_possession::<Ristretto255, Partial>(server.verifying_key(), context, proof),
+        Err(InvalidProof)
+    );
+
+    let mut tampered = proof;
+    tampered.s += curve25519_dalek::Scalar::ONE;
+    assert_eq!(
+        verify_possession::<Ristretto255, Verifiable>(server.verifying_key(), context, tampered),
+        Err(InvalidProof)
+    );
+}
+
+#[test]
+fn repeated_randomness() {
+    let server = Server::<Ristretto255, Verifiable>::random(&mut Rng::new(1));
+    let first = server.prove_possession(Input::try_from(CONTEXT).unwrap(), &mut Rng::new(7));
+    let second = server.prove_possession(
+        Input::try_from(b"another nonce".as_slice()).unwrap(),
+        &mut Rng::new(7),
+    );
+    // The same randomness must not lead to the same nonce for different contexts.
+    let key = server.secret_key().expose_secret();
+    assert_ne!(first.s + first.c * key, second.s + second.c * key);
+}
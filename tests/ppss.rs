@@ -0,0 +1,120 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Evaluated, Input, Proof, VerifyingKey,
+    mode::Partial,
+    ppss::{Backup, BackupKey, PinOutput, PinRequest, RecoveryError},
+    server::Server,
+};
+use suite::{P256, Rng};
+
+mod suite;
+
+type Response = (usize, Evaluated<p256::ProjectivePoint>, Proof<p256::Scalar>);
+
+const IDENTIFIER: &[u8] = b"backup/alice";
+
+fn servers() -> Vec<Server<P256, Partial>> {
+    (0..5)
+        .map(|i| Server::random(&mut Rng::new(100 + i)))
+        .collect()
+}
+
+fn pin_outputs(
+    servers: &[Server<P256, Partial>],
+    queried: &[usize],
+    pin: &[u8],
+) -> Vec<PinOutput<P256>> {
+    let mut rng = Rng::new(7);
+    let verifying_keys: Vec<VerifyingKey<_>> = servers.iter().map(Server::verifying_key).collect();
+    let identifier = Input::try_from(IDENTIFIER).unwrap();
+    let (request, blinded_elements) = PinRequest::new(
+        Input::try_from(pin).unwrap(),
+        identifier,
+        &verifying_keys,
+        &mut rng,
+    )
+    .unwrap();
+
+    let responses: Vec<Response> = queried
+        .iter()
+        .map(|&i| {
+            let ([evaluated_element], proof) = servers[i]
+                .evaluate([blinded_elements[i]], identifier, &mut rng)
+                .unwrap();
+            (i, evaluated_element, proof)
+        })
+        .collect();
+    request.finish(&responses).unwrap()
+}
+
+fn create(servers: &[Server<P256, Partial>]) -> (Backup<P256>, BackupKey<32>) {
+    let outputs = pin_outputs(servers, &[0, 1, 2, 3, 4], b"1234");
+    Backup::create(3, &outputs, &mut Rng::new(8))
+}
+
+#[test]
+fn recover_with_threshold() {
+    let servers = servers();
+    let (backup, key) = create(&servers);
+
+    for queried in [[0, 1, 2], [2, 3, 4], [4, 0, 3]] {
+        let outputs = pin_outputs(&servers, &queried, b"1234");
+        assert_eq!(backup.recover::<32>(&outputs).unwrap(), key);
+    }
+    let outputs = pin_outputs(&servers, &[0, 1, 2, 3, 4], b"1234");
+    assert_eq!(backup.recover::<32>(&outputs).unwrap(), key);
+
+    let restored = Backup::<P256>::from_parts(
+        backup.threshold(),
+        backup.shares().to_vec(),
+        *backup.commitment(),
+    );
+    let outputs = pin_outputs(&servers, &[1, 3, 4], b"1234");
+    assert_eq!(restored.recover::<32>(&outputs).unwrap(), key);
+}
+
+#[test]
+fn recovery_failures() {
+    let servers = servers();
+    let (backup, _) = create(&servers);
+
+    let outputs = pin_outputs(&servers, &[0, 1], b"1234");
+    assert_eq!(
+        backup.recover::<32>(&outputs),
+        Err(RecoveryError::NotEnoughShares)
+    );
+
+    let outputs = pin_outputs(&servers, &[0, 1, 2], b"4321");
+    assert_eq!(backup.recover::<32>(&outputs), Err(RecoveryError::WrongPin));
+
+    let mut outputs = pin_outputs(&servers, &[0, 1, 2], b"1234");
+    outputs[2] = PinOutput {
+        index: outputs[0].index,
+        output: outputs[0].output,
+    };
+    assert_eq!(
+        backup.recover::<32>(&outputs),
+        Err(RecoveryError::DuplicateShare)
+    );
+}
+
+#[test]
+fn misbehaving_server_is_detected() {
+    let servers = servers();
+    let verifying_keys: Vec<_> = servers.iter().map(Server::verifying_key).collect();
+    let identifier = Input::try_from(IDENTIFIER).unwrap();
+    let mut rng = Rng::new(9);
+    let (request, blinded_elements) = PinRequest::<P256>::new(
+        Input::try_from(b"1234".as_slice()).unwrap(),
+        identifier,
+        &verifying_keys,
+        &mut rng,
+    )
+    .unwrap();
+
+    let ([evaluated_element], proof) = servers[1]
+        .evaluate([blinded_elements[0]], identifier, &mut rng)
+        .unwrap();
+    assert!(request.finish(&[(0, evaluated_element, proof)]).is_err());
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input,
+    client::{Client, InvalidProof},
+    mode::{Partial, Verifiable},
+    prepared::PreparedVerifyingKey,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn verifiable() {
+    let mut rng = Rng::new(1);
+    let server = Server::<P256, Verifiable>::random(&mut rng);
+    let prepared = PreparedVerifyingKey::new(server.verifying_key());
+    let inputs = [b"alpha".as_slice(), b"beta", b"gamma"].map(|i| Input::try_from(i).unwrap());
+    let expected = server.full_evaluate(inputs).unwrap();
+
+    for _ in 0..3 {
+        let (client, blinded_elements) =
+            Client::<_, P256, Verifiable>::blind(inputs, server.verifying_key(), &mut rng).unwrap();
+        let (evaluated_elements, proof) = server.evaluate(blinded_elements, &mut rng);
+        let outputs = client
+            .finalize_prepared(evaluated_elements, proof, &prepared)
+            .unwrap();
+        assert_eq!(outputs, expected);
+    }
+
+    let other = Server::<P256, Verifiable>::random(&mut rng);
+    let (client, blinded_elements) =
+        Client::<_, P256, Verifiable>::blind(inputs, server.verifying_key(), &mut rng).unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, &mut rng);
+    assert_eq!(
+        client.finalize_prepared(
+            evaluated_elements,
+            proof,
+            &PreparedVerifyingKey::new(other.verifying_key())
+        ),
+        Err(InvalidProof)
+    );
+}
+
+#[test]
+fn partial() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Partial>::random(&mut rng);
+    let prepared = PreparedVerifyingKey::new(server.verifying_key());
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+
+    for info in [b"first".as_slice(), b"second"] {
+        let info = Input::try_from(info).unwrap();
+        let expected = server.full_evaluate(inputs, info).unwrap();
+
+        let (client, blinded_elements) =
+            Client::<_, Ristretto255, Partial>::blind_prepared(inputs, info, &prepared, &mut rng)
+                .unwrap();
+        let (evaluated_elements, proof) =
+            server.evaluate(blinded_elements, info, &mut rng).unwrap();
+        let outputs = client
+            .finalize_prepared(evaluated_elements, proof, &prepared)
+            .unwrap();
+        assert_eq!(outputs, expected);
+
+        let (client, blinded_elements) = Client::<_, Ristretto255, Partial>::blind(
+            inputs,
+            info,
+            server.verifying_key(),
+            &mut rng,
+        )
+        .unwrap();
+        let (mut evaluated_elements, proof) =
+            server.evaluate(blinded_elements, info, &mut rng).unwrap();
+        evaluated_elements.swap(0, 1);
+        assert_eq!(
+            client.finalize_prepared(evaluated_elements, proof, &prepared),
+            Err(InvalidProof)
+        );
+    }
+}
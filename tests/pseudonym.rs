@@ -0,0 +1,111 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input,
+    mode::Partial,
+    pseudonym::{self, Encoding, FinalizeError, Pseudonymizer},
+    server::Server,
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+const IDENTIFIERS: [&[u8]; 5] = [
+    b"alice@example.com",
+    b"bob@example.com",
+    b"+1 555 0100",
+    b"carol@example.com",
+    b"alice@example.com",
+];
+
+fn pseudonymize<const C: usize>(
+    server: &Server<Ristretto255, Partial>,
+    pseudonymizer: Pseudonymizer<'_, curve25519_dalek::RistrettoPoint>,
+    seed: u64,
+) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let identifiers = IDENTIFIERS.map(|i| Input::try_from(i).unwrap());
+    let (batch, blinded_elements) = pseudonymizer
+        .blind::<Ristretto255, C>(&identifiers, &mut rng)
+        .unwrap();
+    let evaluations =
+        pseudonym::evaluate(server, &blinded_elements, pseudonymizer.context(), &mut rng).unwrap();
+    batch.finalize(&evaluations).unwrap()
+}
+
+#[test]
+fn pseudonyms_are_stable() {
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let context = Input::try_from(b"analytics".as_slice()).unwrap();
+    let pseudonymizer = Pseudonymizer::new(server.verifying_key(), context);
+
+    let pseudonyms = pseudonymize::<2>(&server, pseudonymizer, 2);
+    assert_eq!(pseudonyms.len(), IDENTIFIERS.len());
+    assert_eq!(pseudonyms, pseudonymize::<3>(&server, pseudonymizer, 3));
+    assert_eq!(pseudonyms, pseudonymize::<8>(&server, pseudonymizer, 4));
+    assert_eq!(pseudonyms[0], pseudonyms[4]);
+    assert_ne!(pseudonyms[0], pseudonyms[1]);
+
+    let expected = server
+        .full_evaluate(IDENTIFIERS.map(|i| Input::try_from(i).unwrap()), context)
+        .unwrap()
+        .map(const_hex::encode);
+    assert_eq!(pseudonyms, expected);
+}
+
+#[test]
+fn contexts_are_separated() {
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let first = Pseudonymizer::new(
+        server.verifying_key(),
+        Input::try_from(b"first".as_slice()).unwrap(),
+    );
+    let second = Pseudonymizer::new(
+        server.verifying_key(),
+        Input::try_from(b"second".as_slice()).unwrap(),
+    );
+
+    let first = pseudonymize::<2>(&server, first, 2);
+    let second = pseudonymize::<2>(&server, second, 2);
+    first
+        .iter()
+        .zip(&second)
+        .for_each(|(a, b)| assert_ne!(a, b));
+}
+
+#[test]
+fn output_format() {
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let context = Input::try_from(b"analytics".as_slice()).unwrap();
+    let pseudonymizer = Pseudonymizer::new(server.verifying_key(), context)
+        .with_encoding(Encoding::Base64Url)
+        .with_length(12);
+
+    let pseudonyms = pseudonymize::<2>(&server, pseudonymizer, 2);
+    assert!(pseudonyms.iter().all(|p| p.len() == 16));
+
+    assert_eq!(Encoding::Hex.encode(b"foobar"), "666f6f626172");
+    assert_eq!(Encoding::Base32.encode(b"foobar"), "mzxw6ytboi");
+    assert_eq!(Encoding::Base32.encode(b"fooba"), "mzxw6ytb");
+    assert_eq!(Encoding::Base64Url.encode(b"foob"), "Zm9vYg");
+    assert_eq!(Encoding::Base64Url.encode(&[0xfb, 0xff]), "-_8");
+}
+
+#[test]
+fn length_mismatch() {
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let context = Input::try_from(b"analytics".as_slice()).unwrap();
+    let pseudonymizer = Pseudonymizer::new(server.verifying_key(), context);
+
+    let mut rng = Rng::new(2);
+    let identifiers = IDENTIFIERS.map(|i| Input::try_from(i).unwrap());
+    let (batch, blinded_elements) = pseudonymizer
+        .blind::<Ristretto255, 2>(&identifiers, &mut rng)
+        .unwrap();
+    let evaluations =
+        pseudonym::evaluate(&server, &blinded_elements[1..], context, &mut rng).unwrap();
+    assert_eq!(
+        batch.finalize(&evaluations).err(),
+        Some(FinalizeError::LengthMismatch)
+    );
+}
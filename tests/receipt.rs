@@ -0,0 +1,132 @@
+#![cfg(feature = "alloc")]
+
+use curve25519_dalek::RistrettoPoint;
+use group::Group;
+use oprf::{
+    Evaluated, Input, InvalidEncoding,
+    client::Client,
+    mode::{Partial, Verifiable},
+    receipt::Receipt,
+    server::Server,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn verifiable_receipt() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+    let (client, blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind(inputs, server.verifying_key(), &mut rng)
+            .unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, &mut rng);
+    client.finalize(evaluated_elements, proof).unwrap();
+
+    let receipt = Receipt::<Ristretto255> {
+        verifying_key: server.verifying_key(),
+        blinded_elements: blinded_elements.to_vec(),
+        evaluated_elements: evaluated_elements.to_vec(),
+        info: None,
+        proof,
+        commitments: None,
+    };
+    assert_eq!(receipt.verify(), Ok(()));
+
+    let bytes = receipt.to_bytes();
+    let decoded = Receipt::<Ristretto255>::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, receipt);
+    assert_eq!(decoded.verify(), Ok(()));
+
+    assert_eq!(
+        Receipt::<P256>::from_bytes(&bytes),
+        Err(InvalidEncoding),
+        "suite must match"
+    );
+    assert!(Receipt::<Ristretto255>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    let mut tampered = receipt.clone();
+    tampered.evaluated_elements.swap(0, 1);
+    assert!(tampered.verify().is_err());
+
+    // The identity is rejected when decoding, as in `DeserializeElement` of RFC 9497.
+    let mut identity = receipt.clone();
+    identity.evaluated_elements[0] = Evaluated(RistrettoPoint::identity());
+    assert_eq!(
+        Receipt::<Ristretto255>::from_bytes(&identity.to_bytes()),
+        Err(InvalidEncoding)
+    );
+}
+
+#[test]
+fn partial_receipt() {
+    let mut rng = Rng::new(1);
+    let server = Server::<P256, Partial>::random(&mut rng);
+    let info = Input::try_from(b"issuer log".as_slice()).unwrap();
+    let (client, blinded_elements) = Client::<_, P256, Partial>::blind(
+        [Input::try_from(b"alpha".as_slice()).unwrap()],
+        info,
+        server.verifying_key(),
+        &mut rng,
+    )
+    .unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, info, &mut rng).unwrap();
+    client.finalize(evaluated_elements, proof).unwrap();
+
+    let receipt = Receipt::<P256> {
+        verifying_key: server.verifying_key(),
+        blinded_elements: blinded_elements.to_vec(),
+        evaluated_elements: evaluated_elements.to_vec(),
+        info: Some(info.as_ref().to_vec()),
+        proof,
+        commitments: None,
+    };
+    let decoded = Receipt::<P256>::from_bytes(&receipt.to_bytes()).unwrap();
+    assert_eq!(decoded, receipt);
+    assert_eq!(decoded.verify(), Ok(()));
+
+    let mut tampered = receipt.clone();
+    tampered.info = Some(b"other".to_vec());
+    assert!(tampered.verify().is_err());
+    tampered.info = None;
+    assert!(tampered.verify().is_err());
+}
+
+#[test]
+fn committed_receipt() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let input = Input::try_from(b"alpha".as_slice()).unwrap();
+    let (client, blinded_elements) =
+        Client::<_, Ristretto255, Verifiable>::blind([input], server.verifying_key(), &mut rng)
+            .unwrap();
+    let (evaluated_elements, proof, commitments) =
+        server.evaluate_committed(blinded_elements, &mut rng);
+    client.finalize(evaluated_elements, proof).unwrap();
+
+    let receipt = Receipt::<Ristretto255> {
+        verifying_key: server.verifying_key(),
+        blinded_elements: blinded_elements.to_vec(),
+        evaluated_elements: evaluated_elements.to_vec(),
+        info: None,
+        proof,
+        commitments: Some(commitments),
+    };
+    assert_eq!(receipt.verify(), Ok(()));
+
+    let bytes = receipt.to_bytes();
+    assert_eq!(
+        Receipt::<Ristretto255>::from_bytes(&bytes),
+        Ok(receipt.clone())
+    );
+    assert!(Receipt::<Ristretto255>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    // The commitments must be those of the proof.
+    let mut tampered = receipt.clone();
+    tampered.commitments = Some(oprf::Commitments {
+        t2: commitments.t3,
+        ..commitments
+    });
+    assert!(tampered.verify().is_err());
+}
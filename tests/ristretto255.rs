@@ -2,7 +2,7 @@ use group::ff::PrimeField;
 use oprf::mode::{Base, Partial, Verifiable};
 use std::num::NonZeroU16;
 
-use curve25519_dalek::{RistrettoPoint, Scalar};
+use curve25519_dalek::{RistrettoPoint, Scalar, traits::VartimeMultiscalarMul};
 use digest::consts::U32;
 use hash2curve::{ExpandMsg, ExpandMsgXmd, Expander};
 use sha2::Sha512;
@@ -46,6 +46,10 @@ impl oprf::Suite for Ristretto255 {
             .expect("filling correct size");
         Scalar::from_bytes_mod_order_wide(&uniform_bytes)
     }
+
+    fn multiscalar_mul(scalars: &[Scalar], elements: &[Self::Group]) -> Self::Group {
+        RistrettoPoint::vartime_multiscalar_mul(scalars, elements)
+    }
 }
 
 #[test]
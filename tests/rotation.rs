@@ -0,0 +1,73 @@
+#![cfg(feature = "alloc")]
+
+use oprf::{
+    Input,
+    mode::Verifiable,
+    rotation::{InvalidWindow, KeyError, KeyId, KeyManager},
+    server::Server,
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn rotation() {
+    let mut rng = Rng::new(1);
+    let mut manager = KeyManager::<Ristretto255, Verifiable>::new(20);
+    let first = manager.insert(Server::random(&mut rng), 0, 100).unwrap();
+    let second = manager.insert(Server::random(&mut rng), 90, 200).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(
+        KeyId::new::<Ristretto255>(manager.verifying_key(&first).unwrap().0),
+        first
+    );
+
+    assert_eq!(manager.current(50).unwrap().0, first);
+    assert_eq!(manager.current(95).unwrap().0, second);
+    assert!(manager.current(200).is_none());
+
+    // Evaluations route by key ID, and reject keys outside of their window.
+    assert!(manager.evaluation_key(&first, 95).is_ok());
+    assert_eq!(
+        manager.evaluation_key(&first, 100).err(),
+        Some(KeyError::Expired)
+    );
+    assert_eq!(
+        manager.evaluation_key(&second, 50).err(),
+        Some(KeyError::NotYetActive)
+    );
+    assert_eq!(
+        manager.evaluation_key(&KeyId([0; 32]), 50).err(),
+        Some(KeyError::UnknownKey)
+    );
+
+    // Redemptions are accepted during the grace period.
+    let input = [Input::try_from(b"input".as_slice()).unwrap()];
+    let output = manager
+        .evaluation_key(&first, 50)
+        .unwrap()
+        .full_evaluate(input)
+        .unwrap();
+    let server = manager.redemption_key(&first, 110).unwrap();
+    assert_eq!(server.full_evaluate(input).unwrap(), output);
+    assert_eq!(
+        manager.redemption_key(&first, 120).err(),
+        Some(KeyError::Expired)
+    );
+
+    manager.prune(110);
+    assert_eq!(manager.ids().count(), 2);
+    manager.prune(120);
+    assert_eq!(manager.ids().copied().collect::<Vec<_>>(), [second]);
+}
+
+#[test]
+fn invalid_window() {
+    let mut manager = KeyManager::<Ristretto255, Verifiable>::new(0);
+    assert_eq!(
+        manager
+            .insert(Server::random(&mut Rng::new(1)), 10, 10)
+            .unwrap_err(),
+        InvalidWindow
+    );
+}
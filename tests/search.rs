@@ -0,0 +1,71 @@
+use oprf::{
+    Input,
+    mode::Partial,
+    search::{SearchToken, TokenRequest},
+    server::Server,
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+fn tokens<const N: usize>(
+    server: &Server<Ristretto255, Partial>,
+    keywords: [&[u8]; N],
+    index: &[u8],
+    seed: u64,
+) -> [SearchToken<16, 32>; N] {
+    let mut rng = Rng::new(seed);
+    let index = Input::try_from(index).unwrap();
+    let (request, blinded_elements) = TokenRequest::<_, Ristretto255>::new(
+        keywords.map(|k| Input::try_from(k).unwrap()),
+        index,
+        server.verifying_key(),
+        &mut rng,
+    )
+    .unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, index, &mut rng).unwrap();
+    request.finish(evaluated_elements, proof).unwrap()
+}
+
+#[test]
+fn tokens_are_deterministic() {
+    let server = Server::random(&mut Rng::new(1));
+    let [invoice, contract, other] = tokens(
+        &server,
+        [b"invoice", b"contract", b"invoice"],
+        b"mailbox",
+        2,
+    );
+    assert_eq!(invoice, other);
+    assert_ne!(invoice, contract);
+    assert_ne!(invoice.label[..], invoice.key[..16]);
+
+    let [again] = tokens(&server, [b"invoice"], b"mailbox", 3);
+    assert_eq!(invoice, again);
+}
+
+#[test]
+fn indexes_are_separated() {
+    let server = Server::random(&mut Rng::new(1));
+    let [mailbox] = tokens(&server, [b"invoice"], b"mailbox", 2);
+    let [drive] = tokens(&server, [b"invoice"], b"drive", 2);
+    assert_ne!(mailbox.label, drive.label);
+    assert_ne!(mailbox.key, drive.key);
+}
+
+#[test]
+fn misbehaving_key_server_is_detected() {
+    let server = Server::<Ristretto255, Partial>::random(&mut Rng::new(1));
+    let mut rng = Rng::new(2);
+    let index = Input::try_from(b"mailbox".as_slice()).unwrap();
+    let (request, blinded_elements) = TokenRequest::<_, Ristretto255>::new(
+        [Input::try_from(b"invoice".as_slice()).unwrap()],
+        index,
+        server.verifying_key(),
+        &mut rng,
+    )
+    .unwrap();
+    let other = Input::try_from(b"drive".as_slice()).unwrap();
+    let (evaluated_elements, proof) = server.evaluate(blinded_elements, other, &mut rng).unwrap();
+    assert!(request.finish::<16, 32>(evaluated_elements, proof).is_err());
+}
@@ -0,0 +1,45 @@
+use group::ff::PrimeField;
+use oprf::{
+    Input,
+    client::Client,
+    mode::{Base, Verifiable},
+    server::{SecretKey, Server},
+};
+use suite::{Ristretto255, Rng};
+
+mod suite;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[test]
+fn debug_is_redacted() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let key = hex(server.secret_key().expose_secret().to_repr().as_ref());
+
+    let debug = format!("{:?}", server.secret_key());
+    assert_eq!(debug, "SecretKey(..)");
+    assert!(!debug.contains(&key));
+
+    let inputs = [Input::try_from(b"alpha".as_slice()).unwrap()];
+    let (client, _) = Client::<_, Ristretto255, Base>::blind(inputs, &mut rng).unwrap();
+    let debug = format!("{client:?}");
+    assert!(debug.contains("inputs"));
+    assert!(!debug.contains("blinds"));
+}
+
+#[test]
+fn export() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let other = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    assert_ne!(server.secret_key(), other.secret_key());
+
+    let key = SecretKey::new(*server.secret_key().expose_secret());
+    assert_eq!(&key, server.secret_key());
+    let imported = Server::<Ristretto255, Verifiable>::from_secret_key(key);
+    assert_eq!(imported.secret_key(), server.secret_key());
+    assert_eq!(imported.verifying_key(), server.verifying_key());
+}
@@ -0,0 +1,150 @@
+//! Ciphersuites shared by the tests that are not tied to a specific suite.
+#![allow(dead_code)]
+
+use std::num::NonZeroU16;
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use digest::consts::{U32, U48, U72, U98};
+use hash2curve::{ExpandMsg, ExpandMsgXmd, Expander};
+use p256::ProjectivePoint;
+use sha2::{Sha256, Sha384, Sha512};
+
+pub struct Ristretto255;
+
+impl oprf::Suite for Ristretto255 {
+    type Group = RistrettoPoint;
+
+    const IDENTIFIER: &'static [u8] = b"ristretto255-SHA512";
+
+    type Hash = Sha512;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        let mut expander = <ExpandMsgXmd<Sha512> as ExpandMsg<U32>>::expand_message(
+            hash,
+            domain,
+            NonZeroU16::new(64).expect("64 is non-zero"),
+        )
+        .expect("instantiation is valid");
+        let mut uniform_bytes = [0u8; 64];
+        expander
+            .fill_bytes(&mut uniform_bytes)
+            .expect("filling correct size");
+        RistrettoPoint::from_uniform_bytes(&uniform_bytes)
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        let mut expander = <ExpandMsgXmd<Sha512> as ExpandMsg<U32>>::expand_message(
+            hash,
+            domain,
+            NonZeroU16::new(64).expect("64 is non-zero"),
+        )
+        .expect("instantiation is valid");
+        let mut uniform_bytes = [0u8; 64];
+        expander
+            .fill_bytes(&mut uniform_bytes)
+            .expect("filling correct size");
+        Scalar::from_bytes_mod_order_wide(&uniform_bytes)
+    }
+}
+
+pub struct P256;
+
+impl oprf::Suite for P256 {
+    const IDENTIFIER: &'static [u8] = b"P256-SHA256";
+
+    type Group = ProjectivePoint;
+
+    type Hash = Sha256;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        hash2curve::hash_from_bytes::<p256::NistP256, hash2curve::ExpandMsgXmd<Sha256>>(
+            hash, domain,
+        )
+        .expect("properly constructed")
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        hash2curve::hash_to_scalar::<p256::NistP256, hash2curve::ExpandMsgXmd<Sha256>, U48>(
+            hash, domain,
+        )
+        .expect("properly constructed")
+    }
+}
+
+pub struct P384;
+
+impl oprf::Suite for P384 {
+    const IDENTIFIER: &'static [u8] = b"P384-SHA384";
+
+    type Group = p384::ProjectivePoint;
+
+    type Hash = Sha384;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        hash2curve::hash_from_bytes::<p384::NistP384, hash2curve::ExpandMsgXmd<Sha384>>(
+            hash, domain,
+        )
+        .expect("properly constructed")
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        hash2curve::hash_to_scalar::<p384::NistP384, hash2curve::ExpandMsgXmd<Sha384>, U72>(
+            hash, domain,
+        )
+        .expect("properly constructed")
+    }
+}
+
+pub struct P521;
+
+impl oprf::Suite for P521 {
+    const IDENTIFIER: &'static [u8] = b"P521-SHA512";
+
+    type Group = p521::ProjectivePoint;
+
+    type Hash = Sha512;
+
+    fn hash_to_group(hash: &[&[u8]], domain: &[&[u8]]) -> Self::Group {
+        hash2curve::hash_from_bytes::<p521::NistP521, hash2curve::ExpandMsgXmd<Sha512>>(
+            hash, domain,
+        )
+        .expect("properly constructed")
+    }
+
+    fn hash_to_scalar(hash: &[&[u8]], domain: &[&[u8]]) -> <Self::Group as group::Group>::Scalar {
+        hash2curve::hash_to_scalar::<p521::NistP521, hash2curve::ExpandMsgXmd<Sha512>, U98>(
+            hash, domain,
+        )
+        .expect("properly constructed")
+    }
+}
+
+/// Deterministic RNG for tests that do not rely on specific random values.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+}
+
+impl rand_core::RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // SplitMix64
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+impl rand_core::CryptoRng for Rng {}
@@ -56,7 +56,7 @@ impl<const N: usize, S: Suite> Vectors<N, S, mode::Base> {
         let server =
             Server::<S, mode::Base>::new(self.seed, Input::try_from(&self.info[..]).unwrap())
                 .unwrap();
-        assert_eq!(server.secret_key(), &self.secret_key);
+        assert_eq!(server.secret_key().expose_secret(), &self.secret_key);
 
         for vector in &self.vectors {
             let inputs: [_; N] = vector
@@ -80,6 +80,12 @@ impl<const N: usize, S: Suite> Vectors<N, S, mode::Base> {
                 .iter()
                 .zip(&vector.outputs)
                 .for_each(|(a, b)| assert_eq!(a.as_slice(), b.as_slice(), "outputs should match"));
+
+            let outputs = server.full_evaluate(inputs).unwrap();
+            outputs
+                .iter()
+                .zip(&vector.outputs)
+                .for_each(|(a, b)| assert_eq!(a.as_slice(), b.as_slice(), "outputs should match"));
         }
     }
 }
@@ -89,7 +95,7 @@ impl<const N: usize, S: Suite> Vectors<N, S, mode::Verifiable> {
         let server =
             Server::<S, mode::Verifiable>::new(self.seed, Input::try_from(&self.info[..]).unwrap())
                 .unwrap();
-        assert_eq!(server.secret_key(), &self.secret_key);
+        assert_eq!(server.secret_key().expose_secret(), &self.secret_key);
         assert_eq!(server.verifying_key(), self.verifying_key);
 
         for vector in &self.vectors {
@@ -114,11 +120,21 @@ impl<const N: usize, S: Suite> Vectors<N, S, mode::Verifiable> {
             let (evaluated_elements, proof) = server.evaluate(blinded_elements, &mut rng);
             assert_eq!(evaluated_elements.map(|e| e.0), vector.evaluated_elements);
             assert_eq!(proof, vector.vector_data.proof);
+            assert_eq!(
+                server.evaluate_with_nonce(blinded_elements, vector.vector_data.proof_scalar),
+                (evaluated_elements, proof)
+            );
             let outputs = client.finalize(evaluated_elements, proof).unwrap();
             outputs
                 .iter()
                 .zip(&vector.outputs)
                 .for_each(|(a, b)| assert_eq!(a.as_slice(), b.as_slice(), "outputs should match"));
+
+            let outputs = server.full_evaluate(inputs).unwrap();
+            outputs
+                .iter()
+                .zip(&vector.outputs)
+                .for_each(|(a, b)| assert_eq!(a.as_slice(), b.as_slice(), "outputs should match"));
         }
     }
 }
@@ -128,7 +144,7 @@ impl<const N: usize, S: Suite> Vectors<N, S, mode::Partial> {
         let server =
             Server::<S, mode::Partial>::new(self.seed, Input::try_from(&self.info[..]).unwrap())
                 .unwrap();
-        assert_eq!(server.secret_key(), &self.secret_key);
+        assert_eq!(server.secret_key().expose_secret(), &self.secret_key);
         assert_eq!(server.verifying_key(), self.verifying_key);
 
         for vector in &self.vectors {
@@ -159,11 +175,25 @@ impl<const N: usize, S: Suite> Vectors<N, S, mode::Partial> {
                 server.evaluate(blinded_elements, info, &mut rng).unwrap();
             assert_eq!(evaluated_elements.map(|e| e.0), vector.evaluated_elements);
             assert_eq!(proof, vector.vector_data.verifiable_data.proof);
+            assert_eq!(
+                server.evaluate_with_nonce(
+                    blinded_elements,
+                    info,
+                    vector.vector_data.verifiable_data.proof_scalar
+                ),
+                Ok((evaluated_elements, proof))
+            );
             let outputs = client.finalize(evaluated_elements, proof).unwrap();
             outputs
                 .iter()
                 .zip(&vector.outputs)
                 .for_each(|(a, b)| assert_eq!(a.as_slice(), b.as_slice(), "outputs should match"));
+
+            let outputs = server.full_evaluate(inputs, info).unwrap();
+            outputs
+                .iter()
+                .zip(&vector.outputs)
+                .for_each(|(a, b)| assert_eq!(a.as_slice(), b.as_slice(), "outputs should match"));
         }
     }
 }
@@ -0,0 +1,64 @@
+use oprf::{
+    Input,
+    mode::{Partial, Verifiable},
+    server::Server,
+    vrf,
+};
+use suite::{P256, Ristretto255, Rng};
+
+mod suite;
+
+#[test]
+fn verifiable() {
+    let mut rng = Rng::new(1);
+    let server = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    let inputs = [b"alpha".as_slice(), b"beta"].map(|i| Input::try_from(i).unwrap());
+
+    let (evaluated_elements, proof) = server.prove(inputs, &mut rng).unwrap();
+    let outputs =
+        vrf::verify::<2, Ristretto255>(server.verifying_key(), inputs, evaluated_elements, proof)
+            .unwrap();
+    assert_eq!(outputs, server.full_evaluate(inputs).unwrap());
+
+    let swapped = [inputs[1], inputs[0]];
+    assert!(
+        vrf::verify::<2, Ristretto255>(server.verifying_key(), swapped, evaluated_elements, proof)
+            .is_err()
+    );
+    let other = Server::<Ristretto255, Verifiable>::random(&mut rng);
+    assert!(
+        vrf::verify::<2, Ristretto255>(other.verifying_key(), inputs, evaluated_elements, proof)
+            .is_err()
+    );
+}
+
+#[test]
+fn partial() {
+    let mut rng = Rng::new(1);
+    let server = Server::<P256, Partial>::random(&mut rng);
+    let inputs = [Input::try_from(b"alpha".as_slice()).unwrap()];
+    let info = Input::try_from(b"epoch 1".as_slice()).unwrap();
+
+    let (evaluated_elements, proof) = server.prove(inputs, info, &mut rng).unwrap();
+    let outputs = vrf::verify_partial::<1, P256>(
+        server.verifying_key(),
+        inputs,
+        info,
+        evaluated_elements,
+        proof,
+    )
+    .unwrap();
+    assert_eq!(outputs, server.full_evaluate(inputs, info).unwrap());
+
+    let other_info = Input::try_from(b"epoch 2".as_slice()).unwrap();
+    assert!(
+        vrf::verify_partial::<1, P256>(
+            server.verifying_key(),
+            inputs,
+            other_info,
+            evaluated_elements,
+            proof,
+        )
+        .is_err()
+    );
+}